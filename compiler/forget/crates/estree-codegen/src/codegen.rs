@@ -1,10 +1,15 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use indexmap::IndexMap;
 use quote::{__private::TokenStream, format_ident, quote};
 use serde::{Deserialize, Serialize};
 
 /// Returns prettyplease-formatted Rust source for estree
+///
+/// TODO(follow-up): `rename_all` support on [`Grammar`]/[`Object`]/[`Node`] lets
+/// `ecmascript.json` collapse its per-field `rename` overrides down to a handful of top-level
+/// case-convention declarations, but `ecmascript.json` itself hasn't been migrated to use it
+/// yet — until that follow-up lands, `rename_all` has no effect on the generated code.
 pub fn estree() -> String {
     let src = include_str!("./ecmascript.json");
     let grammar: Grammar = serde_json::from_str(src).unwrap();
@@ -20,6 +25,12 @@ pub struct Grammar {
     pub nodes: IndexMap<String, Node>,
     pub enums: IndexMap<String, Enum>,
     pub operators: IndexMap<String, Operator>,
+
+    /// Default case convention applied to every field's wire name, unless overridden by the
+    /// containing [`Object`]/[`Node`]'s own `rename_all`, or by a field's explicit `rename`. See
+    /// [`rename_field`] for the supported conventions.
+    #[serde(default)]
+    pub rename_all: Option<String>,
 }
 
 impl Grammar {
@@ -29,24 +40,26 @@ impl Grammar {
             nodes,
             enums,
             operators,
+            rename_all,
         } = self;
 
-        let enum_names: HashSet<String> = enums.keys().cloned().collect();
-
         let mut node_names: Vec<_> = nodes.keys().cloned().collect();
         node_names.sort();
 
+        let recursive_components = recursion_components(&objects, &nodes, &enums);
+        let visitors = visitor_codegen(&objects, &nodes, &enums);
+
         let objects: Vec<_> = objects
             .iter()
-            .map(|(name, object)| object.codegen(name))
+            .map(|(name, object)| object.codegen(name, &recursive_components, rename_all.as_deref()))
             .collect();
         let nodes: Vec<_> = nodes
             .iter()
-            .map(|(name, node)| node.codegen(name))
+            .map(|(name, node)| node.codegen(name, &recursive_components, rename_all.as_deref()))
             .collect();
         let enums: Vec<_> = enums
             .iter()
-            .map(|(name, enum_)| enum_.codegen(name, &enum_names))
+            .map(|(name, enum_)| enum_.codegen(name, &enums, &recursive_components))
             .collect();
         let operators: Vec<_> = operators
             .iter()
@@ -65,6 +78,8 @@ impl Grammar {
             #(#enums)*
 
             #(#operators)*
+
+            #visitors
         }
     }
 }
@@ -73,16 +88,25 @@ impl Grammar {
 pub struct Object {
     #[serde(default)]
     pub fields: IndexMap<String, Field>,
+
+    #[serde(default)]
+    pub rename_all: Option<String>,
 }
 
 impl Object {
-    pub fn codegen(&self, name: &str) -> TokenStream {
-        let name = format_ident!("{}", name);
+    pub fn codegen(
+        &self,
+        name: &str,
+        components: &HashMap<String, usize>,
+        default_rename_all: Option<&str>,
+    ) -> TokenStream {
+        let rename_all = self.rename_all.as_deref().or(default_rename_all);
         let fields: Vec<_> = self
             .fields
             .iter()
-            .map(|(name, field)| field.codegen(name))
+            .map(|(field_name, field)| field.codegen(field_name, name, components, rename_all))
             .collect();
+        let name = format_ident!("{}", name);
 
         quote! {
             #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -97,16 +121,25 @@ impl Object {
 pub struct Node {
     #[serde(default)]
     pub fields: IndexMap<String, Field>,
+
+    #[serde(default)]
+    pub rename_all: Option<String>,
 }
 
 impl Node {
-    pub fn codegen(&self, name: &str) -> TokenStream {
-        let name = format_ident!("{}", name);
+    pub fn codegen(
+        &self,
+        name: &str,
+        components: &HashMap<String, usize>,
+        default_rename_all: Option<&str>,
+    ) -> TokenStream {
+        let rename_all = self.rename_all.as_deref().or(default_rename_all);
         let fields: Vec<_> = self
             .fields
             .iter()
-            .map(|(name, field)| field.codegen_node(name))
+            .map(|(field_name, field)| field.codegen_node(field_name, name, components, rename_all))
             .collect();
+        let name = format_ident!("{}", name);
 
         quote! {
             #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -148,10 +181,44 @@ pub struct Field {
 }
 
 impl Field {
-    pub fn codegen(&self, name: &str) -> TokenStream {
+    /// A field only needs a `Box` around its type when the field is a direct (non-plural)
+    /// reference to a type in the *same* strongly-connected component as `container` — ie
+    /// genuine self/mutual recursion. `Vec<T>` is already heap-indirect so plural fields never
+    /// need one, and a reference to an unrelated (non-recursive) type doesn't either.
+    fn is_recursive(&self, container: &str, components: &HashMap<String, usize>) -> bool {
+        if self.plural {
+            return false;
+        }
+        match (components.get(container), components.get(&self.type_)) {
+            (Some(container), Some(field)) => container == field,
+            _ => false,
+        }
+    }
+
+    /// The field's wire name: an explicit `rename` always wins, otherwise it's derived from the
+    /// Rust field name via `rename_all` (if the container or grammar set one), otherwise the
+    /// field name is used as-is.
+    fn wire_name(&self, name: &str, rename_all: Option<&str>) -> Option<String> {
+        self.rename
+            .clone()
+            .or_else(|| rename_all.map(|case| rename_field(name, case)))
+            .filter(|renamed| renamed != name)
+    }
+
+    pub fn codegen(
+        &self,
+        name: &str,
+        container: &str,
+        components: &HashMap<String, usize>,
+        rename_all: Option<&str>,
+    ) -> TokenStream {
+        let rename = self.wire_name(name, rename_all);
         let name = format_ident!("{}", name);
         let type_name = format_ident!("{}", &self.type_);
         let mut type_ = quote!(#type_name);
+        if self.is_recursive(container, components) {
+            type_ = quote!(Box<#type_>);
+        }
         if self.plural {
             if self.nullable_item {
                 type_ = quote!(Option<#type_>);
@@ -173,7 +240,7 @@ impl Field {
                 #field
             }
         }
-        if let Some(rename) = &self.rename {
+        if let Some(rename) = &rename {
             field = quote! {
                 #[serde(rename = #rename)]
                 #field
@@ -188,10 +255,20 @@ impl Field {
         field
     }
 
-    pub fn codegen_node(&self, name: &str) -> TokenStream {
+    pub fn codegen_node(
+        &self,
+        name: &str,
+        container: &str,
+        components: &HashMap<String, usize>,
+        rename_all: Option<&str>,
+    ) -> TokenStream {
+        let rename = self.wire_name(name, rename_all);
         let name = format_ident!("{}", name);
         let type_name = format_ident!("{}", &self.type_);
         let mut type_ = quote!(#type_name);
+        if self.is_recursive(container, components) {
+            type_ = quote!(Box<#type_>);
+        }
         if self.plural {
             if self.nullable_item {
                 type_ = quote!(Option<#type_>);
@@ -219,7 +296,7 @@ impl Field {
                 #field
             }
         }
-        if let Some(rename) = &self.rename {
+        if let Some(rename) = &rename {
             field = quote! {
                 #[serde(rename = #rename)]
                 #field
@@ -236,44 +313,550 @@ pub struct Enum {
 }
 
 impl Enum {
-    pub fn codegen(&self, name: &str, enums: &HashSet<String>) -> TokenStream {
+    pub fn codegen(
+        &self,
+        name: &str,
+        enums: &IndexMap<String, Enum>,
+        components: &HashMap<String, usize>,
+    ) -> TokenStream {
         let mut sorted_variants: Vec<_> = self.variants.iter().collect();
         sorted_variants.sort();
 
-        let name = format_ident!("{}", name);
+        // A variant only needs a `Box` when it's genuinely self/mutually recursive with its
+        // containing enum (same SCC), regardless of whether the variant is itself another enum
+        // or a leaf node/object.
+        let is_recursive = |container: &str, variant: &str| {
+            match (components.get(container), components.get(variant)) {
+                (Some(container), Some(variant)) => container == variant,
+                _ => false,
+            }
+        };
+        let is_recursive_variant = |variant: &str| is_recursive(name, variant);
+
+        let name_ident = format_ident!("{}", name);
         let variants: Vec<_> = sorted_variants
             .iter()
-            .map(|name| {
-                let variant = format_ident!("{}", name);
-                if enums.contains(*name) {
-                    quote!(#variant(#variant))
-                } else {
+            .map(|variant_name| {
+                let variant = format_ident!("{}", variant_name);
+                if is_recursive_variant(variant_name) {
                     quote!(#variant(Box<#variant>))
+                } else {
+                    quote!(#variant(#variant))
                 }
             })
             .collect();
 
-        let enum_ = quote! {
-            pub enum #name {
-                #(#variants),*
-            }
-        };
-        let enum_ = if sorted_variants.iter().any(|name| enums.contains(*name)) {
-            // contains recursive enum, use untagged serialization
+        let contains_nested_enum = sorted_variants.iter().any(|variant| enums.contains_key(*variant));
+
+        if contains_nested_enum {
+            // Some variants are themselves enums (eg `Expression` grouping `Pattern`, which in
+            // turn groups `Identifier`, ...). Serializing is fine with `#[serde(untagged)]`: the
+            // nested enum already emits its own `"type"` tag, so there's nothing left for this
+            // enum to add. Deserializing the same way would try every variant (recursively) in
+            // turn, discarding each failed attempt's error, which is quadratic on large ASTs and
+            // gives useless error messages. Every ESTree node carries a `"type"` discriminant, so
+            // instead peek it once and dispatch straight to the matching (possibly nested)
+            // variant.
+            let mut flattened = flatten_variants(name, enums);
+            flattened.sort_by(|a, b| a.tag.cmp(&b.tag));
+
+            let expected_tags = flattened
+                .iter()
+                .map(|variant| variant.tag.clone())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let match_arms: Vec<_> = flattened
+                .iter()
+                .map(|variant| {
+                    let tag = &variant.tag;
+                    let leaf = format_ident!("{}", variant.tag);
+                    let mut build = quote! {
+                        serde_json::from_value::<#leaf>(value).map_err(serde::de::Error::custom)?
+                    };
+                    for (idx, variant_name) in variant.path.iter().enumerate().rev() {
+                        let wrapper_name = if idx == 0 {
+                            name.to_string()
+                        } else {
+                            variant.path[idx - 1].clone()
+                        };
+                        let wrapper = format_ident!("{}", wrapper_name);
+                        let variant_ident = format_ident!("{}", variant_name);
+                        build = if is_recursive(&wrapper_name, variant_name) {
+                            quote!(#wrapper::#variant_ident(Box::new(#build)))
+                        } else {
+                            quote!(#wrapper::#variant_ident(#build))
+                        };
+                    }
+                    quote!(#tag => Ok(#build))
+                })
+                .collect();
+
             quote! {
+                #[derive(Serialize, Clone, Debug)]
                 #[serde(untagged)]
-                #enum_
+                pub enum #name_ident {
+                    #(#variants),*
+                }
+
+                impl<'de> serde::Deserialize<'de> for #name_ident {
+                    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                    where
+                        D: serde::Deserializer<'de>,
+                    {
+                        let value = serde_json::Value::deserialize(deserializer)?;
+                        let type_ = value
+                            .get("type")
+                            .and_then(serde_json::Value::as_str)
+                            .map(str::to_string)
+                            .ok_or_else(|| serde::de::Error::custom("missing \"type\" field"))?;
+                        match type_.as_str() {
+                            #(#match_arms,)*
+                            other => Err(serde::de::Error::custom(format!(
+                                "unknown type \"{}\", expected one of: {}",
+                                other, #expected_tags
+                            ))),
+                        }
+                    }
+                }
             }
         } else {
             quote! {
+                #[derive(Serialize, Deserialize, Clone, Debug)]
                 #[serde(tag = "type")]
-                #enum_
+                pub enum #name_ident {
+                    #(#variants),*
+                }
+            }
+        }
+    }
+}
+
+/// Converts a Rust snake_case field name into the wire name implied by one of the standard
+/// `serde(rename_all = "...")` conventions. Splits `name` into words on `_`, then recombines them
+/// per `case`. Panics on an unrecognized `case`, since that means the grammar JSON is malformed.
+fn rename_field(name: &str, case: &str) -> String {
+    let words: Vec<&str> = name.split('_').filter(|word| !word.is_empty()).collect();
+
+    fn capitalize(word: &str) -> String {
+        let mut chars = word.chars();
+        match chars.next() {
+            None => String::new(),
+            Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
+        }
+    }
+
+    match case {
+        "camelCase" => words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| if i == 0 { word.to_lowercase() } else { capitalize(word) })
+            .collect(),
+        "PascalCase" => words.iter().map(|word| capitalize(word)).collect(),
+        "snake_case" => words.iter().map(|word| word.to_lowercase()).collect::<Vec<_>>().join("_"),
+        "SCREAMING_SNAKE_CASE" => words.iter().map(|word| word.to_uppercase()).collect::<Vec<_>>().join("_"),
+        "kebab-case" => words.iter().map(|word| word.to_lowercase()).collect::<Vec<_>>().join("-"),
+        "SCREAMING-KEBAB-CASE" => words.iter().map(|word| word.to_uppercase()).collect::<Vec<_>>().join("-"),
+        _ => panic!("unknown rename_all case convention: {case}"),
+    }
+}
+
+/// A concrete (non-enum) type reachable as a variant of some enum, together with the chain of
+/// variant names (outermost to innermost) needed to rebuild the enum nesting down to that leaf.
+/// Enums commonly group other enums as variants (eg `Expression` containing `Pattern`), so a
+/// single JSON `"type"` tag can be several layers of wrapping deep.
+struct FlattenedVariant {
+    tag: String,
+    path: Vec<String>,
+}
+
+fn flatten_variants(name: &str, enums: &IndexMap<String, Enum>) -> Vec<FlattenedVariant> {
+    let mut result = Vec::new();
+    let Some(enum_) = enums.get(name) else {
+        return result;
+    };
+    let mut sorted_variants: Vec<_> = enum_.variants.iter().collect();
+    sorted_variants.sort();
+    for variant in sorted_variants {
+        if enums.contains_key(variant) {
+            for mut nested in flatten_variants(variant, enums) {
+                nested.path.insert(0, variant.clone());
+                result.push(nested);
+            }
+        } else {
+            result.push(FlattenedVariant {
+                tag: variant.clone(),
+                path: vec![variant.clone()],
+            });
+        }
+    }
+    result
+}
+
+/// Emits a `Visitor<'a>`/`VisitMut` trait pair for every generated object, node, and enum type,
+/// plus a `walk_*`/`walk_*_mut` free function per type. Mirrors the `Visitor`/`MutVisitor` design
+/// used for the HIR (see `forget_hir::visitor`): each `visit_*` method defaults to calling the
+/// matching `walk_*` function, so a pass can override just the handful of types it cares about
+/// and fall through to the default recursion for the rest. `loc`/`range` live outside a `Node`'s
+/// `fields` map, so they're never visited.
+fn visitor_codegen(
+    objects: &IndexMap<String, Object>,
+    nodes: &IndexMap<String, Node>,
+    enums: &IndexMap<String, Enum>,
+) -> TokenStream {
+    let generated_types: HashSet<String> = objects
+        .keys()
+        .chain(nodes.keys())
+        .chain(enums.keys())
+        .cloned()
+        .collect();
+
+    let mut methods = Vec::new();
+    let mut methods_mut = Vec::new();
+    let mut walks = Vec::new();
+    let mut walks_mut = Vec::new();
+
+    for (name, object) in objects {
+        methods.push(visit_method(name, false));
+        methods_mut.push(visit_method(name, true));
+        walks.push(walk_struct_fn(name, &object.fields, &generated_types, false));
+        walks_mut.push(walk_struct_fn(name, &object.fields, &generated_types, true));
+    }
+    for (name, node) in nodes {
+        methods.push(visit_method(name, false));
+        methods_mut.push(visit_method(name, true));
+        walks.push(walk_struct_fn(name, &node.fields, &generated_types, false));
+        walks_mut.push(walk_struct_fn(name, &node.fields, &generated_types, true));
+    }
+    for (name, enum_) in enums {
+        methods.push(visit_method(name, false));
+        methods_mut.push(visit_method(name, true));
+        walks.push(walk_enum_fn(name, enum_, false));
+        walks_mut.push(walk_enum_fn(name, enum_, true));
+    }
+
+    quote! {
+        pub trait Visitor<'a> {
+            #(#methods)*
+        }
+
+        #(#walks)*
+
+        pub trait VisitMut {
+            #(#methods_mut)*
+        }
+
+        #(#walks_mut)*
+    }
+}
+
+fn visit_method(name: &str, mutable: bool) -> TokenStream {
+    let name_ident = format_ident!("{}", name);
+    let visit = format_ident!("visit_{}", name);
+    if mutable {
+        let walk = format_ident!("walk_{}_mut", name);
+        quote! {
+            fn #visit(&mut self, node: &mut #name_ident) {
+                #walk(self, node);
+            }
+        }
+    } else {
+        let walk = format_ident!("walk_{}", name);
+        quote! {
+            fn #visit(&mut self, node: &'a #name_ident) {
+                #walk(self, node);
+            }
+        }
+    }
+}
+
+fn walk_struct_fn(
+    name: &str,
+    fields: &IndexMap<String, Field>,
+    generated_types: &HashSet<String>,
+    mutable: bool,
+) -> TokenStream {
+    let name_ident = format_ident!("{}", name);
+    if mutable {
+        let walk = format_ident!("walk_{}_mut", name);
+        let stmts: Vec<_> = fields
+            .iter()
+            .filter_map(|(field_name, field)| visit_field_stmt_mut(field_name, field, generated_types))
+            .collect();
+        quote! {
+            pub fn #walk<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut #name_ident) {
+                #(#stmts)*
+            }
+        }
+    } else {
+        let walk = format_ident!("walk_{}", name);
+        let stmts: Vec<_> = fields
+            .iter()
+            .filter_map(|(field_name, field)| visit_field_stmt(field_name, field, generated_types))
+            .collect();
+        quote! {
+            pub fn #walk<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, node: &'a #name_ident) {
+                #(#stmts)*
+            }
+        }
+    }
+}
+
+/// Builds the traversal statement for a single field, or `None` if the field's type isn't one of
+/// the grammar's own generated types (eg a `String`/`bool`/`JsValue` leaf, which has nothing to
+/// recurse into). Handles every combination of `plural`/`nullable`/`nullable_item` that
+/// [`Field::codegen`] can produce (`T`, `Option<T>`, `Vec<T>`, `Vec<Option<T>>`,
+/// `Option<Vec<T>>`, `Option<Vec<Option<T>>>`).
+fn visit_field_stmt(name: &str, field: &Field, generated_types: &HashSet<String>) -> Option<TokenStream> {
+    if !generated_types.contains(&field.type_) {
+        return None;
+    }
+    let field_ident = format_ident!("{}", name);
+    let visit = format_ident!("visit_{}", field.type_);
+
+    Some(if field.plural {
+        let item_visit = if field.nullable_item {
+            quote! {
+                for item in items {
+                    if let Some(item) = item {
+                        visitor.#visit(item);
+                    }
+                }
+            }
+        } else {
+            quote! {
+                for item in items {
+                    visitor.#visit(item);
+                }
             }
         };
+        if field.nullable {
+            quote! {
+                if let Some(items) = &node.#field_ident {
+                    #item_visit
+                }
+            }
+        } else {
+            quote! {
+                let items = &node.#field_ident;
+                #item_visit
+            }
+        }
+    } else if field.nullable {
+        quote! {
+            if let Some(item) = &node.#field_ident {
+                visitor.#visit(item);
+            }
+        }
+    } else {
+        quote! {
+            visitor.#visit(&node.#field_ident);
+        }
+    })
+}
 
+/// Mutable counterpart of [`visit_field_stmt`]; same shape, `&mut` throughout.
+fn visit_field_stmt_mut(name: &str, field: &Field, generated_types: &HashSet<String>) -> Option<TokenStream> {
+    if !generated_types.contains(&field.type_) {
+        return None;
+    }
+    let field_ident = format_ident!("{}", name);
+    let visit = format_ident!("visit_{}", field.type_);
+
+    Some(if field.plural {
+        let item_visit = if field.nullable_item {
+            quote! {
+                for item in items {
+                    if let Some(item) = item {
+                        visitor.#visit(item);
+                    }
+                }
+            }
+        } else {
+            quote! {
+                for item in items {
+                    visitor.#visit(item);
+                }
+            }
+        };
+        if field.nullable {
+            quote! {
+                if let Some(items) = &mut node.#field_ident {
+                    #item_visit
+                }
+            }
+        } else {
+            quote! {
+                let items = &mut node.#field_ident;
+                #item_visit
+            }
+        }
+    } else if field.nullable {
         quote! {
-            #[derive(Serialize, Deserialize, Clone, Debug)]
-            #enum_
+            if let Some(item) = &mut node.#field_ident {
+                visitor.#visit(item);
+            }
+        }
+    } else {
+        quote! {
+            visitor.#visit(&mut node.#field_ident);
+        }
+    })
+}
+
+fn walk_enum_fn(name: &str, enum_: &Enum, mutable: bool) -> TokenStream {
+    let name_ident = format_ident!("{}", name);
+    let mut sorted_variants: Vec<_> = enum_.variants.iter().collect();
+    sorted_variants.sort();
+
+    if mutable {
+        let walk = format_ident!("walk_{}_mut", name);
+        let arms: Vec<_> = sorted_variants
+            .iter()
+            .map(|variant| {
+                let variant_ident = format_ident!("{}", variant);
+                let visit = format_ident!("visit_{}", variant);
+                quote!(#name_ident::#variant_ident(inner) => visitor.#visit(inner))
+            })
+            .collect();
+        quote! {
+            pub fn #walk<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut #name_ident) {
+                match node {
+                    #(#arms,)*
+                }
+            }
+        }
+    } else {
+        let walk = format_ident!("walk_{}", name);
+        let arms: Vec<_> = sorted_variants
+            .iter()
+            .map(|variant| {
+                let variant_ident = format_ident!("{}", variant);
+                let visit = format_ident!("visit_{}", variant);
+                quote!(#name_ident::#variant_ident(inner) => visitor.#visit(inner))
+            })
+            .collect();
+        quote! {
+            pub fn #walk<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, node: &'a #name_ident) {
+                match node {
+                    #(#arms,)*
+                }
+            }
+        }
+    }
+}
+
+/// Computes, for every object/node/enum type in the grammar, which strongly-connected component
+/// (SCC) it belongs to in the "references" graph: an edge `container -> field_type` for every
+/// non-plural field, and `enum -> variant_type` for every enum variant. `Vec<T>` fields are
+/// already heap-indirect so they don't need a `Box` and are skipped; `Option<T>` fields don't
+/// change a type's size on their own (the niche still needs resolving), so they still get an
+/// edge. A field or variant only needs a `Box` when its type lands in the same SCC as its
+/// container — ie it's part of a genuine (possibly mutual) recursive cycle, not just a reference
+/// to an unrelated type. Tarjan's algorithm finds these components in a single pass.
+fn recursion_components(
+    objects: &IndexMap<String, Object>,
+    nodes: &IndexMap<String, Node>,
+    enums: &IndexMap<String, Enum>,
+) -> HashMap<String, usize> {
+    let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+
+    for (name, object) in objects {
+        let entry = edges.entry(name.clone()).or_default();
+        for field in object.fields.values() {
+            if !field.plural {
+                entry.push(field.type_.clone());
+            }
+        }
+    }
+    for (name, node) in nodes {
+        let entry = edges.entry(name.clone()).or_default();
+        for field in node.fields.values() {
+            if !field.plural {
+                entry.push(field.type_.clone());
+            }
+        }
+    }
+    for (name, enum_) in enums {
+        let entry = edges.entry(name.clone()).or_default();
+        entry.extend(enum_.variants.iter().cloned());
+    }
+
+    Tarjan::new(&edges).run()
+}
+
+/// Tarjan's strongly-connected-components algorithm over the type-reference graph built by
+/// [`recursion_components`]. Each node is assigned the index of its component; two nodes share a
+/// component iff they're mutually reachable (ie part of the same recursive cycle).
+struct Tarjan<'a> {
+    edges: &'a HashMap<String, Vec<String>>,
+    index: HashMap<String, usize>,
+    lowlink: HashMap<String, usize>,
+    on_stack: HashMap<String, bool>,
+    stack: Vec<String>,
+    next_index: usize,
+    next_component: usize,
+    components: HashMap<String, usize>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn new(edges: &'a HashMap<String, Vec<String>>) -> Self {
+        Self {
+            edges,
+            index: HashMap::new(),
+            lowlink: HashMap::new(),
+            on_stack: HashMap::new(),
+            stack: Vec::new(),
+            next_index: 0,
+            next_component: 0,
+            components: HashMap::new(),
+        }
+    }
+
+    fn run(mut self) -> HashMap<String, usize> {
+        let names: Vec<String> = self.edges.keys().cloned().collect();
+        for name in names {
+            if !self.index.contains_key(&name) {
+                self.strongconnect(&name);
+            }
+        }
+        self.components
+    }
+
+    fn strongconnect(&mut self, name: &str) {
+        self.index.insert(name.to_string(), self.next_index);
+        self.lowlink.insert(name.to_string(), self.next_index);
+        self.next_index += 1;
+        self.stack.push(name.to_string());
+        self.on_stack.insert(name.to_string(), true);
+
+        let successors = self.edges.get(name).cloned().unwrap_or_default();
+        for successor in &successors {
+            // References to types outside this grammar (eg primitives) have no node; skip them.
+            if !self.edges.contains_key(successor) {
+                continue;
+            }
+            if !self.index.contains_key(successor) {
+                self.strongconnect(successor);
+                let lowlink = self.lowlink[name].min(self.lowlink[successor]);
+                self.lowlink.insert(name.to_string(), lowlink);
+            } else if *self.on_stack.get(successor).unwrap_or(&false) {
+                let lowlink = self.lowlink[name].min(self.index[successor]);
+                self.lowlink.insert(name.to_string(), lowlink);
+            }
+        }
+
+        if self.lowlink[name] == self.index[name] {
+            let component = self.next_component;
+            self.next_component += 1;
+            loop {
+                let member = self.stack.pop().expect("component is non-empty");
+                self.on_stack.insert(member.clone(), false);
+                self.components.insert(member.clone(), component);
+                if member == name {
+                    break;
+                }
+            }
         }
     }
 }
@@ -347,3 +930,83 @@ impl Operator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(fields: &[(&str, &str)]) -> Node {
+        let mut map = IndexMap::new();
+        for (name, type_) in fields {
+            map.insert(
+                name.to_string(),
+                Field {
+                    type_: type_.to_string(),
+                    nullable: false,
+                    optional: false,
+                    plural: false,
+                    nullable_item: false,
+                    flatten: false,
+                    rename: None,
+                },
+            );
+        }
+        Node { fields: map, rename_all: None }
+    }
+
+    #[test]
+    fn boxes_only_genuinely_recursive_fields() {
+        // A <-> B is a real cycle (mutual recursion); D -> C is one-directional (C doesn't
+        // reference D back), so only A/B should land in the same component.
+        let mut nodes = IndexMap::new();
+        nodes.insert("A".to_string(), node(&[("b", "B")]));
+        nodes.insert("B".to_string(), node(&[("a", "A")]));
+        nodes.insert("C".to_string(), node(&[("leaf", "Leaf")]));
+        nodes.insert("D".to_string(), node(&[("c", "C")]));
+
+        let components = recursion_components(&IndexMap::new(), &nodes, &IndexMap::new());
+
+        assert_eq!(components["A"], components["B"], "A and B form a mutual-recursion cycle");
+        assert_ne!(components["C"], components["D"], "D -> C is one-directional, not a cycle");
+
+        let a_to_b = node(&[("b", "B")]).fields["b"].is_recursive("A", &components);
+        let d_to_c = node(&[("c", "C")]).fields["c"].is_recursive("D", &components);
+        assert!(a_to_b, "A.b: B should be boxed, A and B are mutually recursive");
+        assert!(!d_to_c, "D.c: C should not be boxed, D -> C never cycles back");
+    }
+
+    #[test]
+    fn plural_fields_are_never_boxed_even_when_recursive() {
+        let mut nodes = IndexMap::new();
+        nodes.insert("A".to_string(), node(&[("b", "B")]));
+        nodes.insert("B".to_string(), node(&[("a", "A")]));
+        let components = recursion_components(&IndexMap::new(), &nodes, &IndexMap::new());
+
+        let plural_field = Field {
+            type_: "A".to_string(),
+            nullable: false,
+            optional: false,
+            plural: true,
+            nullable_item: false,
+            flatten: false,
+            rename: None,
+        };
+        assert!(!plural_field.is_recursive("B", &components), "Vec<T> is already heap-indirect");
+    }
+
+    #[test]
+    fn rename_field_covers_every_supported_case_convention() {
+        assert_eq!(rename_field("source_type", "camelCase"), "sourceType");
+        assert_eq!(rename_field("source_type", "PascalCase"), "SourceType");
+        assert_eq!(rename_field("source_type", "snake_case"), "source_type");
+        assert_eq!(rename_field("source_type", "SCREAMING_SNAKE_CASE"), "SOURCE_TYPE");
+        assert_eq!(rename_field("source_type", "kebab-case"), "source-type");
+        assert_eq!(rename_field("source_type", "SCREAMING-KEBAB-CASE"), "SOURCE-TYPE");
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown rename_all case convention")]
+    fn rename_field_panics_on_an_unknown_case_convention() {
+        rename_field("source_type", "Upper Camel Case");
+    }
+}
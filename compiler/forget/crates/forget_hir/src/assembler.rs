@@ -0,0 +1,724 @@
+use std::fmt::Write as _;
+
+use num_bigint::BigInt;
+
+use crate::{
+    Array, Binary, Call, DeclareLocal, Effect, Function, FunctionBody, GotoKind, GotoTerminal,
+    Identifier, IdentifierData, IdentifierId, IdentifierOperand, InstrIx, Instruction,
+    InstructionId, InstructionKind, InstructionValue, LValue, LabelTerminal, LoadGlobal,
+    LoadLocal, MutableRange, Number, Operand, PlaceOrSpread, Primitive, PrimitiveValue,
+    ReactiveScope, ReturnTerminal, ScopeId, StoreLocal, Terminal, TerminalValue,
+};
+
+/// Prints a stable, human-readable textual form of `function`, one instruction per line,
+/// inspired by Krakatau's v2 assembler/disassembler round-trip model for JVM bytecode: the
+/// output of [`disassemble`] can be hand-edited and fed back through [`assemble`] to
+/// reconstruct an equivalent `Function`. This is distinct from the [`crate::Print`] trait,
+/// which produces a dump meant for humans to read but not to reparse.
+///
+/// Not every `InstructionValue`/`TerminalValue` variant round-trips yet (see [`assemble`]);
+/// unsupported ones are still printed (prefixed with `unsupported`) so the dump stays useful
+/// for inspecting compiler state even when it can't be reassembled.
+///
+/// [`assemble`] numbers instructions `#0`, `#1`, ... in the order they appear across blocks, so
+/// a dump only reassembles when the function's block instructions are already numbered that way;
+/// a dump with gaps or out-of-order indices (eg instructions appended by a pass, or ones left
+/// outside every block) is rejected with [`AssembleError::UnexpectedToken`].
+pub fn disassemble(function: &Function) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "function{}{}({}) {{",
+        if function.is_async { " async" } else { "" },
+        if function.is_generator { " generator" } else { "" },
+        function
+            .params
+            .iter()
+            .map(|param| disassemble_identifier(&param.identifier))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+    for block in &function.body.blocks {
+        let _ = writeln!(out, "bb{}:", usize::from(block.id));
+        for &ix in &block.instructions {
+            let instr = &function.body.instructions[usize::from(ix)];
+            let _ = writeln!(out, "  {}", disassemble_instruction(ix, instr));
+        }
+        let _ = writeln!(out, "  {}", disassemble_terminal(&block.terminal));
+    }
+    let _ = writeln!(out, "}}");
+    out
+}
+
+fn disassemble_operand(operand: &Operand) -> String {
+    match operand.effect {
+        Some(effect) => format!("#{}:{}", usize::from(operand.ix), effect),
+        None => format!("#{}", usize::from(operand.ix)),
+    }
+}
+
+fn disassemble_identifier(identifier: &IdentifierOperand) -> String {
+    let data = identifier.identifier.data.borrow();
+    let name = identifier.identifier.name.as_deref().unwrap_or("_");
+    let scope = match &data.scope {
+        Some(scope) => format!("{}", usize::from(scope.id)),
+        None => "-".to_string(),
+    };
+    let mut out = format!(
+        "{name}#{} range={}..{} scope={scope}",
+        usize::from(identifier.identifier.id),
+        usize::from(data.mutable_range.start),
+        usize::from(data.mutable_range.end),
+    );
+    if let Some(effect) = identifier.effect {
+        let _ = write!(out, " effect={effect}");
+    }
+    out
+}
+
+fn disassemble_lvalue(lvalue: &LValue) -> String {
+    format!("{} {}", lvalue.kind, disassemble_identifier(&lvalue.identifier))
+}
+
+fn disassemble_primitive(value: &PrimitiveValue) -> String {
+    match value {
+        PrimitiveValue::BigInt(value) => format!("bigint {value}"),
+        PrimitiveValue::Boolean(value) => format!("boolean {value}"),
+        PrimitiveValue::Null => "null".to_string(),
+        PrimitiveValue::Number(value) => format!("number {}", f64::from(*value)),
+        PrimitiveValue::String(value) => format!("string {value:?}"),
+        PrimitiveValue::Undefined => "undefined".to_string(),
+    }
+}
+
+fn disassemble_instruction(ix: InstrIx, instr: &Instruction) -> String {
+    let prefix = format!("#{} [{}]", usize::from(ix), usize::from(instr.id));
+    match &instr.value {
+        InstructionValue::Array(Array { elements }) => {
+            let elements = elements
+                .iter()
+                .map(|element| match element {
+                    Some(PlaceOrSpread::Place(operand)) => disassemble_operand(operand),
+                    Some(PlaceOrSpread::Spread(operand)) => format!("...{}", disassemble_operand(operand)),
+                    None => "_".to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{prefix} Array [{elements}]")
+        }
+        InstructionValue::Binary(Binary { left, operator, right }) => {
+            format!(
+                "{prefix} Binary {} {} {}",
+                disassemble_operand(left),
+                operator,
+                disassemble_operand(right)
+            )
+        }
+        InstructionValue::Call(Call { callee, arguments }) => {
+            let arguments = arguments
+                .iter()
+                .map(|argument| match argument {
+                    PlaceOrSpread::Place(operand) => disassemble_operand(operand),
+                    PlaceOrSpread::Spread(operand) => format!("...{}", disassemble_operand(operand)),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{prefix} Call {}({arguments})", disassemble_operand(callee))
+        }
+        InstructionValue::DeclareLocal(DeclareLocal { lvalue }) => {
+            format!("{prefix} DeclareLocal {}", disassemble_lvalue(lvalue))
+        }
+        InstructionValue::LoadGlobal(LoadGlobal { name }) => {
+            format!("{prefix} LoadGlobal {name:?}")
+        }
+        InstructionValue::LoadLocal(LoadLocal { place }) => {
+            format!("{prefix} LoadLocal {}", disassemble_identifier(place))
+        }
+        InstructionValue::Primitive(Primitive { value }) => {
+            format!("{prefix} Primitive {}", disassemble_primitive(value))
+        }
+        InstructionValue::StoreLocal(StoreLocal { lvalue, value }) => {
+            format!(
+                "{prefix} StoreLocal {} = {}",
+                disassemble_lvalue(lvalue),
+                disassemble_operand(value)
+            )
+        }
+        InstructionValue::Tombstone => format!("{prefix} Tombstone"),
+        // Not yet representable in the text format (no stable syntax for a nested
+        // function/JSX tree/context cell); still printed so dumps remain inspectable.
+        other => format!("{prefix} unsupported {other:?}", other = DebugInstructionValue(other)),
+    }
+}
+
+/// `InstructionValue` doesn't derive `Display`, and not every variant we fall back to here
+/// necessarily derives `Debug` with a useful name either; this just prints the variant tag.
+struct DebugInstructionValue<'a>(&'a InstructionValue);
+
+impl std::fmt::Debug for DebugInstructionValue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self.0 {
+            InstructionValue::ComputedLoad(_) => "ComputedLoad",
+            InstructionValue::ComputedStore(_) => "ComputedStore",
+            InstructionValue::DeclareContext(_) => "DeclareContext",
+            InstructionValue::Function(_) => "Function",
+            InstructionValue::JSXElement(_) => "JSXElement",
+            InstructionValue::LoadContext(_) => "LoadContext",
+            InstructionValue::Object(_) => "Object",
+            InstructionValue::PropertyLoad(_) => "PropertyLoad",
+            InstructionValue::PropertyStore(_) => "PropertyStore",
+            _ => "?",
+        };
+        f.write_str(name)
+    }
+}
+
+fn disassemble_terminal(terminal: &Terminal) -> String {
+    let prefix = format!("[{}]", usize::from(terminal.id));
+    match &terminal.value {
+        TerminalValue::Goto(GotoTerminal { block, kind }) => {
+            let kind = match kind {
+                GotoKind::Break => "break",
+                GotoKind::Continue => "continue",
+            };
+            format!("{prefix} goto {kind} bb{}", usize::from(*block))
+        }
+        TerminalValue::Label(LabelTerminal { block, fallthrough }) => {
+            let fallthrough = match fallthrough {
+                Some(block) => format!("bb{}", usize::from(*block)),
+                None => "-".to_string(),
+            };
+            format!("{prefix} label body=bb{} fallthrough={fallthrough}", usize::from(*block))
+        }
+        TerminalValue::Return(ReturnTerminal { value }) => {
+            format!("{prefix} return {}", disassemble_operand(value))
+        }
+        _ => format!("{prefix} unsupported terminal"),
+    }
+}
+
+/// Reparses text produced by [`disassemble`] back into a `Function`. Supports the
+/// `Array`/`Binary`/`Call`/`DeclareLocal`/`LoadGlobal`/`LoadLocal`/`Primitive`/`StoreLocal`/
+/// `Tombstone` instructions and the `goto`/`label`/`return` terminals; anything else
+/// (`DeclareContext`, `Function`, `JSXElement`, `LoadContext`, other terminals) is rejected
+/// with [`AssembleError::Unsupported`] rather than silently dropped.
+pub fn assemble(source: &str) -> Result<Function, AssembleError> {
+    Parser::new(source).parse_function()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssembleError {
+    Unsupported(String),
+    UnexpectedToken { expected: String, found: String },
+    UnexpectedEof,
+    InvalidNumber(String),
+}
+
+impl std::fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported(what) => write!(f, "unsupported in textual format: {what}"),
+            Self::UnexpectedToken { expected, found } => {
+                write!(f, "expected {expected}, found `{found}`")
+            }
+            Self::UnexpectedEof => write!(f, "unexpected end of input"),
+            Self::InvalidNumber(text) => write!(f, "invalid number literal: {text}"),
+        }
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+struct Parser<'a> {
+    tokens: Vec<&'a str>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self { tokens: tokenize(source), pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<&'a str, AssembleError> {
+        let token = self.peek().ok_or(AssembleError::UnexpectedEof)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), AssembleError> {
+        let found = self.next()?;
+        if found == expected {
+            Ok(())
+        } else {
+            Err(AssembleError::UnexpectedToken {
+                expected: expected.to_string(),
+                found: found.to_string(),
+            })
+        }
+    }
+
+    fn parse_u32(&mut self) -> Result<u32, AssembleError> {
+        let token = self.next()?;
+        token.parse::<u32>().map_err(|_| AssembleError::InvalidNumber(token.to_string()))
+    }
+
+    /// Parses `#<ix>` or `#<ix>:<effect>`.
+    fn parse_operand(&mut self) -> Result<Operand, AssembleError> {
+        let token = self.next()?;
+        let token = token.strip_prefix('#').ok_or_else(|| AssembleError::UnexpectedToken {
+            expected: "#<ix>".to_string(),
+            found: token.to_string(),
+        })?;
+        let (ix, effect) = match token.split_once(':') {
+            Some((ix, effect)) => (ix, Some(parse_effect(effect)?)),
+            None => (token, None),
+        };
+        let ix = ix.parse::<u32>().map_err(|_| AssembleError::InvalidNumber(ix.to_string()))?;
+        Ok(Operand { ix: InstrIx::new(ix), effect })
+    }
+
+    fn parse_function(&mut self) -> Result<Function, AssembleError> {
+        self.expect("function")?;
+        let mut is_async = false;
+        let mut is_generator = false;
+        loop {
+            match self.peek() {
+                Some("async") => {
+                    is_async = true;
+                    self.pos += 1;
+                }
+                Some("generator") => {
+                    is_generator = true;
+                    self.pos += 1;
+                }
+                _ => break,
+            }
+        }
+        self.expect("(")?;
+        let mut params = Vec::new();
+        while self.peek() != Some(")") {
+            params.push(crate::Param { identifier: self.parse_identifier_operand()? });
+            if self.peek() == Some(",") {
+                self.next()?;
+            }
+        }
+        self.expect(")")?;
+        self.expect("{")?;
+
+        let mut instructions: Vec<Instruction> = Vec::new();
+        let mut blocks = Vec::new();
+        let mut entry = None;
+
+        while self.peek().map(|token| token != "}").unwrap_or(false) {
+            let block_header = self.next()?;
+            let block_id = block_header
+                .strip_prefix("bb")
+                .and_then(|rest| rest.strip_suffix(':'))
+                .and_then(|id| id.parse::<u32>().ok())
+                .ok_or_else(|| AssembleError::UnexpectedToken {
+                    expected: "bb<id>:".to_string(),
+                    found: block_header.to_string(),
+                })?;
+            if entry.is_none() {
+                entry = Some(crate::BlockId::new(block_id));
+            }
+
+            let mut block_instructions = Vec::new();
+            let terminal = loop {
+                let ix = instructions.len() as u32;
+                match self.parse_instruction_or_terminal(InstrIx::new(ix))? {
+                    InstructionOrTerminal::Instruction(instr) => {
+                        instructions.push(instr);
+                        block_instructions.push(InstrIx::new(ix));
+                    }
+                    InstructionOrTerminal::Terminal(terminal) => break terminal,
+                }
+            };
+
+            blocks.push(Box::new(crate::BasicBlock {
+                id: crate::BlockId::new(block_id),
+                instructions: block_instructions,
+                kind: crate::BlockKind::Block,
+                phis: Default::default(),
+                predecessors: Default::default(),
+                terminal,
+            }));
+        }
+        self.expect("}")?;
+
+        Ok(Function {
+            params,
+            is_async,
+            is_generator,
+            body: FunctionBody {
+                entry: entry.ok_or(AssembleError::UnexpectedEof)?,
+                blocks,
+                instructions,
+            },
+        })
+    }
+
+    fn parse_instruction_or_terminal(
+        &mut self,
+        ix: InstrIx,
+    ) -> Result<InstructionOrTerminal, AssembleError> {
+        // Only instructions carry an `#<ix>` operand-index prefix (see `disassemble_instruction`);
+        // terminals go straight to `[<id>]` (see `disassemble_terminal`), so only consume the
+        // prefix when it's actually there instead of requiring it unconditionally.
+        let operand_prefix = format!("#{}", usize::from(ix));
+        if self.peek() == Some(operand_prefix.as_str()) {
+            self.next()?;
+        }
+        self.expect("[")?;
+        let id = InstructionId(self.parse_u32()?);
+        self.expect("]")?;
+        let tag = self.next()?;
+        let value = match tag {
+            "Array" => {
+                self.expect("[")?;
+                let mut elements = Vec::new();
+                while self.peek() != Some("]") {
+                    if self.peek() == Some(",") {
+                        self.pos += 1;
+                        continue;
+                    }
+                    if self.peek() == Some("_") {
+                        self.pos += 1;
+                        elements.push(None);
+                    } else if self.peek().map(|t| t.starts_with("...")).unwrap_or(false) {
+                        let token = self.next()?.strip_prefix("...").unwrap().to_string();
+                        let operand = self.parse_operand_from_str(&token)?;
+                        elements.push(Some(PlaceOrSpread::Spread(operand)));
+                    } else {
+                        let operand = self.parse_operand()?;
+                        elements.push(Some(PlaceOrSpread::Place(operand)));
+                    }
+                }
+                self.expect("]")?;
+                return Ok(InstructionOrTerminal::Instruction(Instruction {
+                    id,
+                    value: InstructionValue::Array(Array { elements }),
+                }));
+            }
+            "Binary" => {
+                let left = self.parse_operand()?;
+                let operator = self.next()?.parse().map_err(|_| AssembleError::Unsupported(
+                    "unknown binary operator".to_string(),
+                ))?;
+                let right = self.parse_operand()?;
+                InstructionValue::Binary(Binary { left, operator, right })
+            }
+            "Call" => {
+                let callee = self.parse_operand()?;
+                self.expect("(")?;
+                let mut arguments = Vec::new();
+                while self.peek() != Some(")") {
+                    if self.peek() == Some(",") {
+                        self.pos += 1;
+                        continue;
+                    }
+                    if self.peek().map(|t| t.starts_with("...")).unwrap_or(false) {
+                        let token = self.next()?.strip_prefix("...").unwrap().to_string();
+                        arguments.push(PlaceOrSpread::Spread(self.parse_operand_from_str(&token)?));
+                    } else {
+                        arguments.push(PlaceOrSpread::Place(self.parse_operand()?));
+                    }
+                }
+                self.expect(")")?;
+                InstructionValue::Call(Call { callee, arguments })
+            }
+            "DeclareLocal" => {
+                let lvalue = self.parse_lvalue()?;
+                InstructionValue::DeclareLocal(DeclareLocal { lvalue })
+            }
+            "LoadGlobal" => {
+                let name = self.parse_quoted_string()?;
+                InstructionValue::LoadGlobal(LoadGlobal { name })
+            }
+            "LoadLocal" => {
+                let place = self.parse_identifier_operand()?;
+                InstructionValue::LoadLocal(LoadLocal { place })
+            }
+            "Primitive" => InstructionValue::Primitive(Primitive { value: self.parse_primitive()? }),
+            "StoreLocal" => {
+                let lvalue = self.parse_lvalue()?;
+                self.expect("=")?;
+                let value = self.parse_operand()?;
+                InstructionValue::StoreLocal(StoreLocal { lvalue, value })
+            }
+            "Tombstone" => InstructionValue::Tombstone,
+            "goto" => {
+                let kind = match self.next()? {
+                    "break" => GotoKind::Break,
+                    "continue" => GotoKind::Continue,
+                    other => {
+                        return Err(AssembleError::Unsupported(format!("goto kind `{other}`")))
+                    }
+                };
+                let block = self.parse_block_id()?;
+                return Ok(InstructionOrTerminal::Terminal(Terminal {
+                    id,
+                    value: TerminalValue::Goto(GotoTerminal { block, kind }),
+                }));
+            }
+            "label" => {
+                let body = self.next()?;
+                let body = body.strip_prefix("body=").ok_or_else(|| AssembleError::UnexpectedToken {
+                    expected: "body=bb<id>".to_string(),
+                    found: body.to_string(),
+                })?;
+                let block = parse_block_id_str(body)?;
+
+                let fallthrough = self.next()?;
+                let fallthrough =
+                    fallthrough.strip_prefix("fallthrough=").ok_or_else(|| AssembleError::UnexpectedToken {
+                        expected: "fallthrough=bb<id>|-".to_string(),
+                        found: fallthrough.to_string(),
+                    })?;
+                let fallthrough = if fallthrough == "-" {
+                    None
+                } else {
+                    Some(parse_block_id_str(fallthrough)?)
+                };
+                return Ok(InstructionOrTerminal::Terminal(Terminal {
+                    id,
+                    value: TerminalValue::Label(LabelTerminal { block, fallthrough }),
+                }));
+            }
+            "return" => {
+                let value = self.parse_operand()?;
+                return Ok(InstructionOrTerminal::Terminal(Terminal {
+                    id,
+                    value: TerminalValue::Return(ReturnTerminal { value }),
+                }));
+            }
+            other => return Err(AssembleError::Unsupported(other.to_string())),
+        };
+        Ok(InstructionOrTerminal::Instruction(Instruction { id, value }))
+    }
+
+    fn parse_operand_from_str(&self, token: &str) -> Result<Operand, AssembleError> {
+        let token = token.strip_prefix('#').ok_or_else(|| AssembleError::UnexpectedToken {
+            expected: "#<ix>".to_string(),
+            found: token.to_string(),
+        })?;
+        let (ix, effect) = match token.split_once(':') {
+            Some((ix, effect)) => (ix, Some(parse_effect(effect)?)),
+            None => (token, None),
+        };
+        let ix = ix.parse::<u32>().map_err(|_| AssembleError::InvalidNumber(ix.to_string()))?;
+        Ok(Operand { ix: InstrIx::new(ix), effect })
+    }
+
+    fn parse_block_id(&mut self) -> Result<crate::BlockId, AssembleError> {
+        let token = self.next()?;
+        parse_block_id_str(token)
+    }
+
+    fn parse_quoted_string(&mut self) -> Result<String, AssembleError> {
+        let token = self.next()?;
+        if token.starts_with('"') && token.ends_with('"') && token.len() >= 2 {
+            Ok(token[1..token.len() - 1].to_string())
+        } else {
+            Err(AssembleError::UnexpectedToken {
+                expected: "quoted string".to_string(),
+                found: token.to_string(),
+            })
+        }
+    }
+
+    fn parse_identifier_operand(&mut self) -> Result<IdentifierOperand, AssembleError> {
+        let token = self.next()?;
+        let (name, id) = token.split_once('#').ok_or_else(|| AssembleError::UnexpectedToken {
+            expected: "<name>#<id>".to_string(),
+            found: token.to_string(),
+        })?;
+        let id = id.parse::<u32>().map_err(|_| AssembleError::InvalidNumber(id.to_string()))?;
+        let range = self.parse_range()?;
+        let scope = self.parse_scope()?;
+        let effect = if self.peek().map(|t| t.starts_with("effect=")).unwrap_or(false) {
+            let token = self.next()?;
+            Some(parse_effect(token.strip_prefix("effect=").unwrap())?)
+        } else {
+            None
+        };
+        Ok(IdentifierOperand {
+            identifier: Identifier {
+                id: IdentifierId::new(id),
+                name: if name == "_" { None } else { Some(name.to_string()) },
+                data: std::rc::Rc::new(std::cell::RefCell::new(IdentifierData {
+                    mutable_range: range,
+                    scope,
+                    type_: crate::Type::Var(crate::TypeVarId::new(0)),
+                })),
+            },
+            effect,
+        })
+    }
+
+    fn parse_lvalue(&mut self) -> Result<LValue, AssembleError> {
+        let kind = match self.next()? {
+            "Const" => InstructionKind::Const,
+            "Let" => InstructionKind::Let,
+            "Reassign" => InstructionKind::Reassign,
+            other => return Err(AssembleError::Unsupported(format!("lvalue kind `{other}`"))),
+        };
+        let identifier = self.parse_identifier_operand()?;
+        Ok(LValue { identifier, kind })
+    }
+
+    /// Parses the single token `range=<start>..<end>`.
+    fn parse_range(&mut self) -> Result<MutableRange, AssembleError> {
+        let token = self.next()?;
+        let token = token.strip_prefix("range=").ok_or_else(|| AssembleError::UnexpectedToken {
+            expected: "range=<start>..<end>".to_string(),
+            found: token.to_string(),
+        })?;
+        let (start, end) = token.split_once("..").ok_or_else(|| AssembleError::UnexpectedToken {
+            expected: "range=<start>..<end>".to_string(),
+            found: token.to_string(),
+        })?;
+        let start = start.parse::<u32>().map_err(|_| AssembleError::InvalidNumber(start.to_string()))?;
+        let end = end.parse::<u32>().map_err(|_| AssembleError::InvalidNumber(end.to_string()))?;
+        Ok(MutableRange { start: InstructionId(start), end: InstructionId(end) })
+    }
+
+    /// Parses the single token `scope=<id>` or `scope=-`.
+    fn parse_scope(&mut self) -> Result<Option<ReactiveScope>, AssembleError> {
+        let token = self.next()?;
+        let token = token.strip_prefix("scope=").ok_or_else(|| AssembleError::UnexpectedToken {
+            expected: "scope=<id>".to_string(),
+            found: token.to_string(),
+        })?;
+        if token == "-" {
+            Ok(None)
+        } else {
+            let id = token.parse::<u32>().map_err(|_| AssembleError::InvalidNumber(token.to_string()))?;
+            Ok(Some(ReactiveScope { id: ScopeId::new(id), range: MutableRange::new() }))
+        }
+    }
+
+    fn parse_primitive(&mut self) -> Result<PrimitiveValue, AssembleError> {
+        let tag = self.next()?;
+        match tag {
+            "bigint" => {
+                let token = self.next()?;
+                let value = token
+                    .parse::<BigInt>()
+                    .map_err(|_| AssembleError::InvalidNumber(token.to_string()))?;
+                Ok(PrimitiveValue::BigInt(value))
+            }
+            "boolean" => Ok(PrimitiveValue::Boolean(self.next()? == "true")),
+            "null" => Ok(PrimitiveValue::Null),
+            "undefined" => Ok(PrimitiveValue::Undefined),
+            "number" => {
+                let token = self.next()?;
+                let value =
+                    token.parse::<f64>().map_err(|_| AssembleError::InvalidNumber(token.to_string()))?;
+                Ok(PrimitiveValue::Number(Number::from(value)))
+            }
+            "string" => Ok(PrimitiveValue::String(self.parse_quoted_string()?)),
+            other => Err(AssembleError::Unsupported(format!("primitive kind `{other}`"))),
+        }
+    }
+}
+
+enum InstructionOrTerminal {
+    Instruction(Instruction),
+    Terminal(Terminal),
+}
+
+fn parse_block_id_str(token: &str) -> Result<crate::BlockId, AssembleError> {
+    token
+        .strip_prefix("bb")
+        .and_then(|id| id.parse::<u32>().ok())
+        .map(crate::BlockId::new)
+        .ok_or_else(|| AssembleError::UnexpectedToken {
+            expected: "bb<id>".to_string(),
+            found: token.to_string(),
+        })
+}
+
+fn parse_effect(token: &str) -> Result<Effect, AssembleError> {
+    match token {
+        "capture" => Ok(Effect::Capture),
+        "mutate?" => Ok(Effect::ConditionallyMutate),
+        "freeze" => Ok(Effect::Freeze),
+        "mutate" => Ok(Effect::Mutate),
+        "read" => Ok(Effect::Read),
+        "store" => Ok(Effect::Store),
+        other => Err(AssembleError::Unsupported(format!("effect `{other}`"))),
+    }
+}
+
+/// Splits `source` into whitespace-separated tokens, treating `(`, `)`, `[`, `]`, `{`, and `,`
+/// as their own tokens (except inside quoted strings) so the parser above doesn't need a
+/// hand-rolled character-level lexer. Note that `=` is NOT its own token: `key=value` pairs (eg
+/// `range=0..5`, `effect=capture`) are parsed as a single whitespace-delimited token via
+/// `strip_prefix`, so they must stay surrounded by whitespace (or block punctuation) on both
+/// sides in hand-edited input, the same as [`disassemble`] always emits them.
+fn tokenize(source: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let bytes = source.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i] as char;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '"' {
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i] as char != '"' {
+                i += 1;
+            }
+            i += 1;
+            tokens.push(&source[start..i.min(bytes.len())]);
+        } else if "()[]{},".contains(c) {
+            tokens.push(&source[i..i + 1]);
+            i += 1;
+        } else {
+            let start = i;
+            while i < bytes.len() && !(bytes[i] as char).is_whitespace() && !"()[]{},".contains(bytes[i] as char)
+            {
+                i += 1;
+            }
+            tokens.push(&source[start..i]);
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "function(x#0 range=0..3 scope=-) {\n\
+        bb0:\n\
+        #0 [1] Primitive number 1\n\
+        #1 [2] Binary #0 + #0\n\
+        [3] return #1\n\
+        }\n";
+
+    #[test]
+    fn round_trips_params_through_disassemble_and_assemble() {
+        let function = assemble(SOURCE).expect("fixture should assemble");
+        assert_eq!(function.params.len(), 1);
+
+        let redumped = disassemble(&function);
+        let reparsed = assemble(&redumped).expect("disassemble output should reassemble");
+        assert_eq!(disassemble(&reparsed), redumped, "disassemble(assemble(x)) should be a fixpoint");
+    }
+
+    #[test]
+    fn assemble_rejects_unsupported_instructions() {
+        let source = "function() {\nbb0:\n#0 [1] Tombstone\n[2] return #0\n}\n";
+        assert!(assemble(source).is_ok());
+
+        let source = "function() {\nbb0:\n#0 [1] Object []\n[2] return #0\n}\n";
+        assert!(matches!(assemble(source), Err(AssembleError::Unsupported(_))));
+    }
+}
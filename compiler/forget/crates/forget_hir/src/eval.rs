@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::{
+    BasicBlock, BlockId, Function, IdentifierId, InstrIx, InstructionValue, Number, Operand,
+    PrimitiveValue, TerminalValue,
+};
+
+/// Executes a lowered HIR `Function` against a set of bound argument values, returning the
+/// value it evaluates to. This gives the compiler an executable oracle: lowering and
+/// optimization passes (eg [`crate::fold_constants`]) can be tested by checking that `eval`
+/// produces the same result before and after the pass runs.
+///
+/// Only the primitive subset of the IR is supported (see [`RuntimeError`] for what isn't);
+/// `ConditionallyMutate`/`Mutate` effects are threaded through as no-ops, since this
+/// interpreter doesn't yet model aliasing. `Array` and `Call` are explicitly out of scope for
+/// this first cut, not merely unimplemented: `PrimitiveValue` has no array or function value to
+/// hold their results, so there's nothing yet for them to evaluate to.
+pub fn eval(
+    function: &Function,
+    arguments: &[PrimitiveValue],
+    globals: &HashMap<String, PrimitiveValue>,
+) -> Result<PrimitiveValue, RuntimeError> {
+    let mut scope = ScopeStack::new();
+    for (param, value) in function.params.iter().zip(arguments.iter()) {
+        scope.declare(param.identifier.identifier.id, value.clone());
+    }
+
+    let blocks: HashMap<BlockId, &BasicBlock> =
+        function.body.blocks.iter().map(|block| (block.id, block.as_ref())).collect();
+
+    // InstrIx -> the value that instruction evaluated to, populated as we go (mirrors the
+    // `known` side table in `fold_constants`, just computed at runtime instead of statically).
+    let mut values: HashMap<InstrIx, PrimitiveValue> = HashMap::new();
+
+    let mut current = function.body.entry;
+    loop {
+        let block = blocks
+            .get(&current)
+            .ok_or(RuntimeError::UnknownBlock(current))?;
+
+        for &instr_ix in &block.instructions {
+            let instr = &function.body.instructions[usize::from(instr_ix)];
+            let value = match &instr.value {
+                InstructionValue::Primitive(primitive) => primitive.value.clone(),
+                InstructionValue::Binary(binary) => {
+                    let left = resolve(function, &values, &binary.left)?;
+                    let right = resolve(function, &values, &binary.right)?;
+                    eval_binary(&binary.operator.to_string(), &left, &right)?
+                }
+                InstructionValue::DeclareLocal(instr) => {
+                    scope.declare(instr.lvalue.identifier.identifier.id, PrimitiveValue::Undefined);
+                    PrimitiveValue::Undefined
+                }
+                InstructionValue::StoreLocal(instr) => {
+                    let value = resolve(function, &values, &instr.value)?;
+                    scope.store(instr.lvalue.identifier.identifier.id, value.clone())?;
+                    value
+                }
+                InstructionValue::LoadLocal(instr) => {
+                    scope.load(instr.place.identifier.id)?
+                }
+                InstructionValue::LoadGlobal(instr) => globals
+                    .get(&instr.name)
+                    .cloned()
+                    .ok_or_else(|| RuntimeError::UndefinedGlobal(instr.name.clone()))?,
+                // Out of scope for this first cut, not merely unimplemented: `PrimitiveValue`
+                // has no array value to hold `Array`'s result, and no function value (builtin
+                // or user-defined) for `Call` to invoke.
+                InstructionValue::Array(_) => {
+                    return Err(RuntimeError::UnsupportedInstruction("Array"))
+                }
+                InstructionValue::Call(_) => {
+                    return Err(RuntimeError::UnsupportedInstruction("Call"))
+                }
+                InstructionValue::Function(_) => {
+                    return Err(RuntimeError::UnsupportedInstruction("Function"))
+                }
+                InstructionValue::JSXElement(_) => {
+                    return Err(RuntimeError::UnsupportedInstruction("JSXElement"))
+                }
+                InstructionValue::DeclareContext(_) => {
+                    return Err(RuntimeError::UnsupportedInstruction("DeclareContext"))
+                }
+                InstructionValue::LoadContext(_) => {
+                    return Err(RuntimeError::UnsupportedInstruction("LoadContext"))
+                }
+                // None of these produce a `PrimitiveValue` today (there's no object/member
+                // representation in the value domain yet), so they're unsupported rather
+                // than silently approximated.
+                InstructionValue::Object(_) => {
+                    return Err(RuntimeError::UnsupportedInstruction("Object"))
+                }
+                InstructionValue::PropertyLoad(_) => {
+                    return Err(RuntimeError::UnsupportedInstruction("PropertyLoad"))
+                }
+                InstructionValue::PropertyStore(_) => {
+                    return Err(RuntimeError::UnsupportedInstruction("PropertyStore"))
+                }
+                InstructionValue::ComputedLoad(_) => {
+                    return Err(RuntimeError::UnsupportedInstruction("ComputedLoad"))
+                }
+                InstructionValue::ComputedStore(_) => {
+                    return Err(RuntimeError::UnsupportedInstruction("ComputedStore"))
+                }
+                // A dead instruction (eg an input that `fold_constants` folded away) produces no
+                // value; reading one is reported by `resolve`.
+                InstructionValue::Tombstone => continue,
+            };
+            values.insert(instr_ix, value);
+        }
+
+        match &block.terminal.value {
+            TerminalValue::Return(terminal) => return resolve(function, &values, &terminal.value),
+            TerminalValue::Goto(terminal) => current = terminal.block,
+            TerminalValue::Label(terminal) => current = terminal.block,
+            other => return Err(RuntimeError::UnsupportedTerminal(format!("{:?}", other))),
+        }
+    }
+}
+
+/// Binds locals to values while evaluating a function, mirroring the scope-stack design used
+/// by tree-walking interpreters such as schala's evaluator: a stack of frames keyed by
+/// `IdentifierId`. `declare` introduces a fresh binding in the innermost frame; `store`
+/// updates whichever frame already owns the binding (falling back outward), matching how
+/// `DeclareLocal`/`StoreLocal` are used by the lowering passes.
+struct ScopeStack {
+    frames: Vec<HashMap<IdentifierId, PrimitiveValue>>,
+}
+
+impl ScopeStack {
+    fn new() -> Self {
+        Self { frames: vec![HashMap::new()] }
+    }
+
+    fn declare(&mut self, id: IdentifierId, value: PrimitiveValue) {
+        self.frames
+            .last_mut()
+            .expect("scope stack always has at least one frame")
+            .insert(id, value);
+    }
+
+    fn store(&mut self, id: IdentifierId, value: PrimitiveValue) -> Result<(), RuntimeError> {
+        for frame in self.frames.iter_mut().rev() {
+            if frame.contains_key(&id) {
+                frame.insert(id, value);
+                return Ok(());
+            }
+        }
+        Err(RuntimeError::UndefinedLocal(id))
+    }
+
+    fn load(&self, id: IdentifierId) -> Result<PrimitiveValue, RuntimeError> {
+        for frame in self.frames.iter().rev() {
+            if let Some(value) = frame.get(&id) {
+                return Ok(value.clone());
+            }
+        }
+        Err(RuntimeError::UndefinedLocal(id))
+    }
+}
+
+fn resolve(
+    function: &Function,
+    values: &HashMap<InstrIx, PrimitiveValue>,
+    operand: &Operand,
+) -> Result<PrimitiveValue, RuntimeError> {
+    values.get(&operand.ix).cloned().ok_or_else(|| {
+        match function.body.instructions.get(usize::from(operand.ix)).map(|instr| &instr.value) {
+            Some(InstructionValue::Tombstone) => RuntimeError::UnsupportedInstruction("Tombstone"),
+            _ => RuntimeError::UnboundValue(operand.ix),
+        }
+    })
+}
+
+fn eval_binary(
+    operator: &str,
+    left: &PrimitiveValue,
+    right: &PrimitiveValue,
+) -> Result<PrimitiveValue, RuntimeError> {
+    let unsupported = || RuntimeError::UnsupportedOperator(operator.to_string());
+    match operator {
+        "+" => match (left, right) {
+            (PrimitiveValue::Number(left), PrimitiveValue::Number(right)) => {
+                Ok(PrimitiveValue::Number(*left + *right))
+            }
+            (PrimitiveValue::String(left), PrimitiveValue::String(right)) => {
+                Ok(PrimitiveValue::String(format!("{left}{right}")))
+            }
+            _ => Err(unsupported()),
+        },
+        "-" | "*" | "/" => match (left, right) {
+            (PrimitiveValue::Number(left), PrimitiveValue::Number(right)) => {
+                let (left, right) = (*left, *right);
+                Ok(PrimitiveValue::Number(match operator {
+                    "-" => left - right,
+                    "*" => left * right,
+                    "/" => left / right,
+                    _ => unreachable!(),
+                }))
+            }
+            _ => Err(unsupported()),
+        },
+        "==" => left.loosely_equals(right).map(PrimitiveValue::Boolean).ok_or_else(unsupported),
+        "!=" => left
+            .not_loosely_equals(right)
+            .map(PrimitiveValue::Boolean)
+            .ok_or_else(unsupported),
+        "===" => Ok(PrimitiveValue::Boolean(left.strictly_equals(right))),
+        "!==" => Ok(PrimitiveValue::Boolean(left.not_strictly_equals(right))),
+        "<" | "<=" | ">" | ">=" => match (left, right) {
+            (PrimitiveValue::Number(left), PrimitiveValue::Number(right)) => {
+                let (left, right) = (f64::from(*left), f64::from(*right));
+                Ok(PrimitiveValue::Boolean(match operator {
+                    "<" => left < right,
+                    "<=" => left <= right,
+                    ">" => left > right,
+                    ">=" => left >= right,
+                    _ => unreachable!(),
+                }))
+            }
+            _ => Err(unsupported()),
+        },
+        _ => Err(unsupported()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeError {
+    /// The instruction's value isn't representable by the primitive-only interpreter.
+    UnsupportedInstruction(&'static str),
+
+    /// The terminal isn't supported yet (the interpreter only follows `Goto`/`Label`/`Return`).
+    UnsupportedTerminal(String),
+
+    /// The binary operator/operand combination isn't handled.
+    UnsupportedOperator(String),
+
+    /// Jumped to a block id that isn't present in the function.
+    UnknownBlock(BlockId),
+
+    /// Read of a local that was never declared (or is out of scope).
+    UndefinedLocal(IdentifierId),
+
+    /// Read of a global that the caller didn't provide a binding for.
+    UndefinedGlobal(String),
+
+    /// Read of an operand whose defining instruction hasn't produced a value yet.
+    UnboundValue(InstrIx),
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedInstruction(name) => {
+                write!(f, "unsupported instruction: {name}")
+            }
+            Self::UnsupportedTerminal(name) => write!(f, "unsupported terminal: {name}"),
+            Self::UnsupportedOperator(operator) => {
+                write!(f, "unsupported operator/operand combination: {operator}")
+            }
+            Self::UnknownBlock(block) => write!(f, "jumped to unknown block {block:?}"),
+            Self::UndefinedLocal(id) => write!(f, "read of undeclared local {id:?}"),
+            Self::UndefinedGlobal(name) => write!(f, "read of undefined global '{name}'"),
+            Self::UnboundValue(ix) => write!(f, "operand {ix:?} has no value yet"),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assemble;
+
+    #[test]
+    fn binds_parameters_and_evaluates_a_binary_expression() {
+        let source = "function(x#0 range=0..2 scope=-) {\n\
+            bb0:\n\
+            #0 [1] LoadLocal x#0 range=0..2 scope=-\n\
+            #1 [2] Primitive number 1\n\
+            #2 [3] Binary #0 + #1\n\
+            [4] return #2\n\
+            }\n";
+        let function = assemble(source).expect("fixture should assemble");
+
+        let result = eval(&function, &[PrimitiveValue::Number(Number::from(2.0))], &HashMap::new())
+            .expect("eval should succeed");
+
+        assert_eq!(result, PrimitiveValue::Number(Number::from(3.0)));
+    }
+
+    #[test]
+    fn out_of_scope_instructions_are_reported_rather_than_miscomputed() {
+        let source = "function() {\nbb0:\n#0 [1] Tombstone\n[2] return #0\n}\n";
+        let function = assemble(source).expect("fixture should assemble");
+
+        let err = eval(&function, &[], &HashMap::new()).unwrap_err();
+
+        assert_eq!(err, RuntimeError::UnsupportedInstruction("Tombstone"));
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use num_bigint::BigInt;
+
+use crate::{Function, InstrIx, Instruction, InstructionValue, Number, Primitive, PrimitiveValue};
+
+/// Statically evaluates `Binary` instructions whose operands are known at compile time
+/// (i.e. resolve to `Primitive` values), replacing them with the folded `Primitive` result.
+/// Operands that become unused as a result of folding are tombstoned.
+///
+/// This is a conservative pass: any instruction whose operand value isn't known, or whose
+/// operator/operand combination isn't handled below, is left untouched.
+pub fn fold_constants(function: &mut Function) {
+    let instructions = &mut function.body.instructions;
+
+    // Every instruction's operands, counted up front so that we know when an operand
+    // that we fold away has no remaining consumers and can be tombstoned.
+    let mut use_count: HashMap<InstrIx, usize> = HashMap::new();
+    for instr in instructions.iter_mut() {
+        instr.each_operand(|operand| {
+            *use_count.entry(operand.ix).or_insert(0) += 1;
+        });
+    }
+
+    let mut known: HashMap<InstrIx, PrimitiveValue> = HashMap::new();
+    for block in &function.body.blocks {
+        for &instr_ix in &block.instructions {
+            let index = usize::from(instr_ix);
+            match &instructions[index].value {
+                InstructionValue::Primitive(primitive) => {
+                    known.insert(instr_ix, primitive.value.clone());
+                }
+                InstructionValue::Binary(binary) => {
+                    let left = known.get(&binary.left.ix).cloned();
+                    let right = known.get(&binary.right.ix).cloned();
+                    let (left_ix, right_ix) = (binary.left.ix, binary.right.ix);
+                    let operator = binary.operator.to_string();
+                    let folded = match (left, right) {
+                        (Some(left), Some(right)) => fold_binary(&operator, &left, &right),
+                        _ => None,
+                    };
+                    if let Some(folded) = folded {
+                        known.insert(instr_ix, folded.clone());
+                        instructions[index].value =
+                            InstructionValue::Primitive(Primitive { value: folded });
+                        tombstone_if_dead(instructions, &mut use_count, left_ix);
+                        tombstone_if_dead(instructions, &mut use_count, right_ix);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Decrements the use count of `ix` and, if it has no remaining consumers, replaces its
+/// instruction with a `Tombstone` so that later passes (eg DCE) can reclaim it.
+fn tombstone_if_dead(
+    instructions: &mut [Instruction],
+    use_count: &mut HashMap<InstrIx, usize>,
+    ix: InstrIx,
+) {
+    if let Some(count) = use_count.get_mut(&ix) {
+        if *count > 0 {
+            *count -= 1;
+        }
+        if *count == 0 {
+            instructions[usize::from(ix)].value = InstructionValue::Tombstone;
+        }
+    }
+}
+
+/// Evaluates `left operator right` when both operands are statically known, following the
+/// same coercion rules as the corresponding JS binary operators. Returns `None` for any
+/// operator/operand combination we don't (yet) fold, in which case the caller must leave
+/// the original instruction in place.
+fn fold_binary(operator: &str, left: &PrimitiveValue, right: &PrimitiveValue) -> Option<PrimitiveValue> {
+    match operator {
+        "+" => match (left, right) {
+            (PrimitiveValue::Number(left), PrimitiveValue::Number(right)) => {
+                Some(PrimitiveValue::Number(*left + *right))
+            }
+            (PrimitiveValue::BigInt(left), PrimitiveValue::BigInt(right)) => {
+                Some(PrimitiveValue::BigInt(left.clone() + right.clone()))
+            }
+            (PrimitiveValue::String(_), _) | (_, PrimitiveValue::String(_)) => Some(
+                PrimitiveValue::String(format!("{}{}", to_js_string(left)?, to_js_string(right)?)),
+            ),
+            // Mixing BigInt and Number throws a TypeError at runtime; we decline to fold
+            // rather than guess which side "wins".
+            _ => None,
+        },
+        "-" | "*" | "/" => match (left, right) {
+            (PrimitiveValue::BigInt(left), PrimitiveValue::BigInt(right)) => {
+                if operator == "/" && *right == BigInt::from(0) {
+                    // BigInt division by zero throws a RangeError; leave it unfolded.
+                    return None;
+                }
+                Some(PrimitiveValue::BigInt(match operator {
+                    "-" => left.clone() - right.clone(),
+                    "*" => left.clone() * right.clone(),
+                    "/" => left.clone() / right.clone(),
+                    _ => unreachable!(),
+                }))
+            }
+            _ => {
+                let (left, right) = (to_number(left)?, to_number(right)?);
+                Some(PrimitiveValue::Number(match operator {
+                    "-" => left - right,
+                    "*" => left * right,
+                    "/" => left / right,
+                    _ => unreachable!(),
+                }))
+            }
+        },
+        "==" => left.loosely_equals(right).map(PrimitiveValue::Boolean),
+        "!=" => left.not_loosely_equals(right).map(PrimitiveValue::Boolean),
+        "===" => Some(PrimitiveValue::Boolean(left.strictly_equals(right))),
+        "!==" => Some(PrimitiveValue::Boolean(left.not_strictly_equals(right))),
+        "<" | "<=" | ">" | ">=" => {
+            let (PrimitiveValue::Number(left), PrimitiveValue::Number(right)) = (left, right) else {
+                return None;
+            };
+            let (left, right) = (f64::from(*left), f64::from(*right));
+            let result = match operator {
+                "<" => left < right,
+                "<=" => left <= right,
+                ">" => left > right,
+                ">=" => left >= right,
+                _ => unreachable!(),
+            };
+            Some(PrimitiveValue::Boolean(result))
+        }
+        _ => None,
+    }
+}
+
+/// JS `ToNumber` for the primitive subset we fold. Returns `None` for combinations that
+/// aren't well-defined as a finite, unambiguous number (we simply decline to fold those).
+fn to_number(value: &PrimitiveValue) -> Option<Number> {
+    match value {
+        PrimitiveValue::Number(value) => Some(*value),
+        PrimitiveValue::Boolean(true) => Some(Number::from(1.0)),
+        PrimitiveValue::Boolean(false) => Some(Number::from(0.0)),
+        PrimitiveValue::Null => Some(Number::from(0.0)),
+        PrimitiveValue::Undefined => Some(Number::from(f64::NAN)),
+        // Rust's parser also accepts `inf`/`infinity` spellings that JS reads as NaN, so an
+        // infinite parse result isn't folded.
+        PrimitiveValue::String(value) => value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| !value.is_infinite())
+            .map(Number::from),
+        // BigInt can't be coerced to Number here: mixing the two in arithmetic is a
+        // TypeError in JS, so callers must branch on BigInt themselves rather than fall
+        // through to this coercion.
+        PrimitiveValue::BigInt(_) => None,
+    }
+}
+
+/// JS `ToString` for the primitive subset we fold.
+fn to_js_string(value: &PrimitiveValue) -> Option<String> {
+    match value {
+        PrimitiveValue::String(value) => Some(value.clone()),
+        PrimitiveValue::Boolean(value) => Some(value.to_string()),
+        PrimitiveValue::Null => Some("null".to_string()),
+        PrimitiveValue::Undefined => Some("undefined".to_string()),
+        PrimitiveValue::Number(value) => {
+            let value = f64::from(*value);
+            if value.is_nan() {
+                Some("NaN".to_string())
+            } else if value.is_infinite() {
+                Some(if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string())
+            } else if value == 0.0 {
+                // JS prints both `0` and `-0` as "0".
+                Some("0".to_string())
+            } else if value.abs() >= 1e21 || value.abs() < 1e-6 {
+                // JS switches to exponent notation here and Rust's `Display` doesn't; not folded.
+                None
+            } else {
+                Some(value.to_string())
+            }
+        }
+        PrimitiveValue::BigInt(value) => Some(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assemble, eval};
+
+    #[test]
+    fn folding_preserves_eval_semantics() {
+        let source = "function() {\n\
+            bb0:\n\
+            #0 [1] Primitive number 1\n\
+            #1 [2] Primitive number 2\n\
+            #2 [3] Binary #0 + #1\n\
+            [4] return #2\n\
+            }\n";
+        let mut function = assemble(source).expect("fixture should assemble");
+
+        let before = eval(&function, &[], &HashMap::new()).expect("eval should succeed before folding");
+        fold_constants(&mut function);
+        let after = eval(&function, &[], &HashMap::new()).expect("eval should succeed after folding");
+
+        assert_eq!(before, after, "folding a Binary must not change what the function evaluates to");
+        assert_eq!(after, PrimitiveValue::Number(Number::from(3.0)));
+    }
+
+    #[test]
+    fn fold_binary_preserves_number_equality_edge_cases() {
+        // NaN is never equal to itself, even though both operands resolve to the same
+        // canonicalized bit pattern.
+        let nan = PrimitiveValue::Number(Number::from(f64::NAN));
+        assert_eq!(fold_binary("===", &nan, &nan), Some(PrimitiveValue::Boolean(false)));
+
+        // -0.0 and 0.0 compare equal under ==/===, matching IEEE 754 (and JS) semantics.
+        let zero = PrimitiveValue::Number(Number::from(0.0));
+        let neg_zero = PrimitiveValue::Number(Number::from(-0.0));
+        assert_eq!(fold_binary("===", &zero, &neg_zero), Some(PrimitiveValue::Boolean(true)));
+    }
+
+    #[test]
+    fn declines_to_fold_when_an_operand_is_unknown() {
+        let source = "function() {\n\
+            bb0:\n\
+            #0 [1] LoadGlobal \"x\"\n\
+            #1 [2] Primitive number 1\n\
+            #2 [3] Binary #0 + #1\n\
+            [4] return #2\n\
+            }\n";
+        let mut function = assemble(source).expect("fixture should assemble");
+
+        fold_constants(&mut function);
+
+        assert!(matches!(
+            function.body.instructions[2].value,
+            InstructionValue::Binary(_)
+        ));
+    }
+}
@@ -6,24 +6,37 @@ use bumpalo::collections::String;
 use forget_diagnostics::Diagnostic;
 
 use crate::{
-    initialize_hir, BasicBlock, BlockRewriter, BlockRewriterAction, DeclareLocal, Environment,
-    Function, GotoKind, GotoTerminal, Identifier, IdentifierData, IdentifierOperand, InstrIx,
-    Instruction, InstructionKind, InstructionValue, LValue, LabelTerminal, LoadLocal, MutableRange,
-    Operand, PlaceOrSpread, ReturnTerminal, StoreLocal, Terminal, TerminalValue, Type,
+    initialize_hir, BasicBlock, BlockId, DeclareLocal, Environment, Function, FunctionExpression,
+    GotoKind, GotoTerminal, Identifier, IdentifierData, IdentifierOperand, InstrIx, Instruction,
+    InstructionId, InstructionKind, InstructionValue, LValue, LabelTerminal, LoadLocal,
+    MutableRange, Operand, PlaceOrSpread, ReturnTerminal, StoreLocal, Terminal, TerminalValue,
+    Type,
 };
 
+/// A zero/one-parameter immediately-invoked function expression may not accept more parameters
+/// than this; see [`validate_inline_candidate`].
+const MAX_INLINE_PARAMS: usize = 1;
+
+/// Identifies a single call expression to inline: the block that contains it, its position
+/// within that block's instructions, and the id of the `Call` instruction itself. The call's
+/// arguments are read directly from `fun.body` at inlining time rather than duplicated here, so
+/// this stays a cheap, copyable handle that a scan pass can collect and hand off to
+/// [`inline_function_call`] afterwards.
+#[derive(Clone, Copy, Debug)]
+pub struct CallSite {
+    pub block: BlockId,
+    pub index: usize,
+    pub instr_ix: InstrIx,
+    pub instr_id: InstructionId,
+}
+
 /// Inlines `useMemo()` calls, rewriting so that the lambda body becomes part of the
-/// outer block's instructions. To account for complex control flow, the inlining works
-/// as follows:
-/// * First, block ids are guaranteed to be unique for all blocks within a function and
-///   its recursive function expressions. Thus, the function expression's blocks can be
-///   directly moved into the outer function's `blocks` map.
-/// * To account for complex control flow, we create a "label" terminal just prior to
-///   the useMemo call, with the useMemo function's entry block as the body of the
-///   label terminal. The code following the useMemo call becomes the fallthrough.
-///   All returns within the useMemo are translated to instead:
-///   * Assign to a temporary identifier representing the useMemo result
-///   * Break to the label's fallthrough.
+/// outer block's instructions. This is a thin wrapper around [`inline_function_call`]: it scans
+/// for `useMemo(lambda)` call sites specifically and delegates the actual splicing to it.
+///
+/// `useMemo` itself never passes arguments to its callback, so (unlike a general IIFE) the
+/// callback is required to take zero parameters; that check is specific to `useMemo` and so
+/// lives here rather than in the shared [`validate_inline_candidate`].
 ///
 /// ## Example
 ///
@@ -69,17 +82,12 @@ pub fn inline_use_memo<'a>(
 ) -> Result<(), Diagnostic> {
     let mut use_memo_globals: HashSet<InstrIx> = Default::default();
     let mut functions: HashSet<InstrIx> = Default::default();
+    let mut call_sites: Vec<(CallSite, InstrIx)> = Vec::new();
 
-    let blocks = &mut fun.body.blocks;
-    let instructions = &mut fun.body.instructions;
-    let mut rewriter = BlockRewriter::new(blocks, fun.body.entry);
-
-    let mut inlined = Vec::new();
-
-    rewriter.try_each_block(|mut block, rewriter| {
-        for (i, instr_ix) in block.instructions.iter().cloned().enumerate() {
-            let instr = &mut instructions[usize::from(instr_ix)];
-            match &mut instr.value {
+    for block in &fun.body.blocks {
+        for (index, &instr_ix) in block.instructions.iter().enumerate() {
+            let instr = &fun.body.instructions[usize::from(instr_ix)];
+            match &instr.value {
                 InstructionValue::LoadGlobal(value) => {
                     if value.name.as_str() == "useMemo" {
                         use_memo_globals.insert(instr_ix);
@@ -93,7 +101,7 @@ pub fn inline_use_memo<'a>(
                         continue;
                     }
                     // Skip useMemo calls where the argument is a spread element
-                    let lambda_ix = match &value.arguments.get(0) {
+                    let lambda_ix = match value.arguments.get(0) {
                         Some(PlaceOrSpread::Place(place)) => place.ix,
                         _ => continue,
                     };
@@ -101,158 +109,305 @@ pub fn inline_use_memo<'a>(
                     if !functions.contains(&lambda_ix) {
                         continue;
                     }
-                    let instr_id = instr.id;
-
-                    // Create a temporary variable to store the useMemo result into
-                    let temporary_id = env.next_identifier_id();
-                    let temporary = Identifier {
-                        id: temporary_id,
-                        // NOTE: for memoization to work correctly this variable has to be named
-                        name: Some(String::from_str_in("t", &env.allocator)),
-                        data: Rc::new(RefCell::new(IdentifierData {
-                            mutable_range: MutableRange::new(),
-                            scope: None,
-                            type_: Type::Var(env.next_type_var_id()),
-                        })),
-                    };
-                    // Replace the call with a load of the temporary
-                    // this is convenient since consumers of the useMemo call
-                    // already point to this instruction id, so by reusing the
-                    // instruction we don't have to update the consumer(s) to
-                    // look at a different instruction
-                    instr.value = InstructionValue::LoadLocal(LoadLocal {
-                        place: IdentifierOperand {
-                            identifier: temporary.clone(),
-                            effect: None,
+                    call_sites.push((
+                        CallSite {
+                            block: block.id,
+                            index,
+                            instr_ix,
+                            instr_id: instr.id,
                         },
-                    });
+                        lambda_ix,
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
 
-                    // Move the function expression out of its instruction so that we own
-                    // the value and can modify and inline its contents into the outer
-                    // function. We replace with a tombstone value that we can filter out later
-                    let lambda = std::mem::replace(
-                        &mut instructions[usize::from(lambda_ix)].value,
-                        InstructionValue::Tombstone,
-                    );
-                    let mut lambda = if let InstructionValue::Function(lambda) = lambda {
-                        lambda
-                    } else {
-                        unreachable!("Must be a function, checked above")
-                    };
+    if call_sites.is_empty() {
+        return Ok(());
+    }
 
-                    // Additional validation
-                    // TODO: this should be part of a separate validation pass
-                    if !lambda.lowered_function.params.is_empty() {
-                        return Err(Diagnostic::invalid_react(
-                            "useMemo callbacks may not accept any arguments",
-                            None,
-                        ));
-                    }
-                    if lambda.lowered_function.is_async || lambda.lowered_function.is_generator {
-                        return Err(Diagnostic::invalid_react(
-                            "useMemo callbacks may not be async or generator functions",
-                            None,
-                        ));
-                    }
+    for (call_site, lambda_ix) in call_sites {
+        // Move the function expression out of its instruction so that we own the value and can
+        // modify and inline its contents into the outer function. We replace with a tombstone
+        // value that we can filter out later.
+        let lambda = std::mem::replace(
+            &mut fun.body.instructions[usize::from(lambda_ix)].value,
+            InstructionValue::Tombstone,
+        );
+        let lambda = if let InstructionValue::Function(lambda) = lambda {
+            lambda
+        } else {
+            unreachable!("Must be a function, checked above")
+        };
+
+        if !lambda.lowered_function.params.is_empty() {
+            return Err(Diagnostic::invalid_react(
+                "useMemo callbacks may not accept any arguments",
+                None,
+            ));
+        }
 
-                    // Set aside a BlockId for the code that follows the useMemo call
-                    let continuation_block_id = env.next_block_id();
+        inline_function_call(env, fun, call_site, lambda)?;
+    }
 
-                    // Rewrite the body of the lambda to replace any return terminals
-                    // with an assignment to the useMemo temporary followed by a break
-                    // to the continuation block
-                    for block in lambda.lowered_function.body.blocks.iter_mut() {
-                        if let TerminalValue::Return(ReturnTerminal { value }) =
-                            &mut block.terminal.value
-                        {
-                            let store_ix = InstrIx::new(
-                                lambda.lowered_function.body.instructions.len() as u32,
-                            );
-                            lambda.lowered_function.body.instructions.push(Instruction {
-                                id: instr_id,
-                                value: InstructionValue::StoreLocal(StoreLocal {
-                                    lvalue: LValue {
-                                        identifier: IdentifierOperand {
-                                            identifier: temporary.clone(),
-                                            effect: None,
-                                        },
-                                        kind: InstructionKind::Reassign,
-                                    },
-                                    value: Operand {
-                                        ix: value.ix,
-                                        effect: None,
-                                    },
-                                }),
-                            });
-                            block.instructions.push(store_ix);
-                            block.terminal.value = TerminalValue::Goto(GotoTerminal {
-                                block: continuation_block_id,
-                                kind: GotoKind::Break,
-                            });
-                        }
-                    }
+    initialize_hir(&mut fun.body)?;
 
-                    // Extract the block's original terminal, which we will move to the
-                    // continuation block. Replace it with a label terminal, necessary to
-                    // allow the goto statements to have a target.
-                    let terminal_id = block.terminal.id;
-                    let terminal = std::mem::replace(
-                        &mut block.terminal,
-                        Terminal {
-                            id: terminal_id,
-                            value: TerminalValue::Label(LabelTerminal {
-                                block: lambda.lowered_function.body.entry,
-                                fallthrough: Some(continuation_block_id),
-                            }),
+    Ok(())
+}
+
+/// Inlines an immediately-invoked function expression at `call_site`, splicing `lambda`'s blocks
+/// into `fun`. This is the general form of the splicing [`inline_use_memo`] used to do directly:
+/// block ids are guaranteed unique across a function and its nested function expressions, so
+/// `lambda`'s blocks can be moved into `fun` wholesale via `fun.body.inline`. To account for
+/// complex control flow, a "label" terminal is spliced in just before the call, with the
+/// lambda's entry block as the label's body and the code following the call as its fallthrough;
+/// every `Return` inside the lambda is rewritten to instead store into a temporary and break to
+/// the fallthrough.
+///
+/// Beyond what `useMemo` needed, this also binds call arguments to the lambda's parameters: each
+/// argument is stored into a fresh local (a `DeclareLocal` followed by a `StoreLocal`, same as
+/// any other local binding) in the outer block, right before control hands off to the lambda's
+/// entry block, so small helper IIFEs and `useCallback` bodies can be inlined too, not just
+/// zero-argument `useMemo` callbacks.
+///
+/// Callers are expected to have already extracted `lambda` out of its original `Function`
+/// instruction (replacing it with a `Tombstone`); [`validate_inline_candidate`] is run here
+/// regardless.
+pub fn inline_function_call<'a>(
+    env: &Environment<'a>,
+    fun: &mut Function<'a>,
+    call_site: CallSite,
+    mut lambda: FunctionExpression<'a>,
+) -> Result<(), Diagnostic> {
+    validate_inline_candidate(&lambda, &call_site)?;
+
+    // Read the original call's arguments before we overwrite the instruction below.
+    let arguments = match &fun.body.instructions[usize::from(call_site.instr_ix)].value {
+        InstructionValue::Call(call) => call.arguments.clone(),
+        _ => unreachable!("call_site must point at a Call instruction"),
+    };
+    if lambda.lowered_function.params.len() > arguments.len() {
+        return Err(Diagnostic::invalid_react(
+            "cannot inline a call with fewer arguments than the function has parameters",
+            None,
+        ));
+    }
+
+    // Create a temporary variable to store the call's result into
+    let temporary_id = env.next_identifier_id();
+    let temporary = Identifier {
+        id: temporary_id,
+        // NOTE: for memoization to work correctly this variable has to be named
+        name: Some(String::from_str_in("t", &env.allocator)),
+        data: Rc::new(RefCell::new(IdentifierData {
+            mutable_range: MutableRange::new(),
+            scope: None,
+            type_: Type::Var(env.next_type_var_id()),
+        })),
+    };
+
+    // Replace the call with a load of the temporary. This is convenient since consumers of the
+    // call already point to this instruction id, so by reusing the instruction we don't have to
+    // update the consumer(s) to look at a different instruction.
+    fun.body.instructions[usize::from(call_site.instr_ix)].value =
+        InstructionValue::LoadLocal(LoadLocal {
+            place: IdentifierOperand {
+                identifier: temporary.clone(),
+                effect: None,
+            },
+        });
+
+    // Set aside a BlockId for the code that follows the call
+    let continuation_block_id = env.next_block_id();
+
+    // Rewrite the body of the lambda to replace any return terminals with an assignment to the
+    // temporary followed by a break to the continuation block
+    for block in lambda.lowered_function.body.blocks.iter_mut() {
+        if let TerminalValue::Return(ReturnTerminal { value }) = &mut block.terminal.value {
+            let store_ix = InstrIx::new(lambda.lowered_function.body.instructions.len() as u32);
+            lambda.lowered_function.body.instructions.push(Instruction {
+                id: call_site.instr_id,
+                value: InstructionValue::StoreLocal(StoreLocal {
+                    lvalue: LValue {
+                        identifier: IdentifierOperand {
+                            identifier: temporary.clone(),
+                            effect: None,
                         },
-                    );
+                        kind: InstructionKind::Reassign,
+                    },
+                    value: Operand {
+                        ix: value.ix,
+                        effect: None,
+                    },
+                }),
+            });
+            block.instructions.push(store_ix);
+            block.terminal.value = TerminalValue::Goto(GotoTerminal {
+                block: continuation_block_id,
+                kind: GotoKind::Break,
+            });
+        }
+    }
 
-                    // Extract the instructions for the continuation block
-                    let continuation_instructions = block.instructions.split_off(i);
+    let block_idx = fun
+        .body
+        .blocks
+        .iter()
+        .position(|block| block.id == call_site.block)
+        .expect("call_site.block must exist in the function");
 
-                    // Declare the temporary variable at the end of the block preceding
-                    // the useMemo invocation
-                    let declare_ix = InstrIx::new(instructions.len() as u32);
-                    instructions.push(Instruction {
-                        id: instr_id,
-                        value: InstructionValue::DeclareLocal(DeclareLocal {
-                            lvalue: LValue {
-                                identifier: IdentifierOperand {
-                                    identifier: temporary.clone(),
-                                    effect: None,
-                                },
-                                kind: InstructionKind::Let,
-                            },
-                        }),
-                    });
-                    block.instructions.push(declare_ix);
+    // Extract the block's original terminal, which we will move to the continuation block.
+    // Replace it with a label terminal, necessary to allow the goto statements to have a target.
+    let terminal_id = fun.body.blocks[block_idx].terminal.id;
+    let terminal = std::mem::replace(
+        &mut fun.body.blocks[block_idx].terminal,
+        Terminal {
+            id: terminal_id,
+            value: TerminalValue::Label(LabelTerminal {
+                block: lambda.lowered_function.body.entry,
+                fallthrough: Some(continuation_block_id),
+            }),
+        },
+    );
 
-                    // Add the continuation block
-                    let continuation_block = Box::new(BasicBlock {
-                        id: continuation_block_id,
-                        instructions: continuation_instructions,
-                        kind: block.kind,
-                        phis: env.vec_new(),
-                        predecessors: Default::default(),
-                        terminal,
-                    });
-                    rewriter.add_block(continuation_block);
+    // Extract the instructions for the continuation block
+    let continuation_instructions =
+        fun.body.blocks[block_idx].instructions.split_off(call_site.index);
 
-                    inlined.push(lambda);
-                    break;
-                }
-                _ => {}
+    // Declare the temporary variable at the end of the block preceding the call
+    let declare_ix = InstrIx::new(fun.body.instructions.len() as u32);
+    fun.body.instructions.push(Instruction {
+        id: call_site.instr_id,
+        value: InstructionValue::DeclareLocal(DeclareLocal {
+            lvalue: LValue {
+                identifier: IdentifierOperand {
+                    identifier: temporary.clone(),
+                    effect: None,
+                },
+                kind: InstructionKind::Let,
+            },
+        }),
+    });
+    fun.body.blocks[block_idx].instructions.push(declare_ix);
+
+    // Bind each argument to the lambda's matching parameter. These instructions read operands
+    // that belong to the outer function (`fun.body`'s own instruction numbering), so they must
+    // be emitted into `fun`'s own block rather than the lambda's — pushing them onto the
+    // lambda's instructions here would have them get renumbered (and their outer-pointing
+    // operands corrupted) when `fun.body.inline` below splices the lambda's instructions in.
+    // They run right before the block's terminal hands control to the lambda's entry block, the
+    // same spot as the temporary's `DeclareLocal` above.
+    for (param, argument) in lambda.lowered_function.params.iter().zip(arguments.iter()) {
+        let argument = match argument {
+            PlaceOrSpread::Place(operand) => operand.clone(),
+            PlaceOrSpread::Spread(_) => {
+                return Err(Diagnostic::invalid_react(
+                    "cannot inline a call whose argument is a spread element",
+                    None,
+                ))
             }
-        }
-        Ok(BlockRewriterAction::Keep(block))
-    })?;
+        };
 
-    if !inlined.is_empty() {
-        for lambda in inlined {
-            fun.body.inline(lambda);
-        }
-        initialize_hir(&mut fun.body)?;
+        let declare_ix = InstrIx::new(fun.body.instructions.len() as u32);
+        fun.body.instructions.push(Instruction {
+            id: call_site.instr_id,
+            value: InstructionValue::DeclareLocal(DeclareLocal {
+                lvalue: LValue {
+                    identifier: IdentifierOperand {
+                        identifier: param.identifier.clone(),
+                        effect: None,
+                    },
+                    kind: InstructionKind::Let,
+                },
+            }),
+        });
+        fun.body.blocks[block_idx].instructions.push(declare_ix);
+
+        let store_ix = InstrIx::new(fun.body.instructions.len() as u32);
+        fun.body.instructions.push(Instruction {
+            id: call_site.instr_id,
+            value: InstructionValue::StoreLocal(StoreLocal {
+                lvalue: LValue {
+                    identifier: IdentifierOperand {
+                        identifier: param.identifier.clone(),
+                        effect: None,
+                    },
+                    kind: InstructionKind::Reassign,
+                },
+                value: argument,
+            }),
+        });
+        fun.body.blocks[block_idx].instructions.push(store_ix);
     }
 
+    // Add the continuation block
+    let continuation_block = Box::new(BasicBlock {
+        id: continuation_block_id,
+        instructions: continuation_instructions,
+        kind: fun.body.blocks[block_idx].kind,
+        phis: env.vec_new(),
+        predecessors: Default::default(),
+        terminal,
+    });
+    fun.body.blocks.push(continuation_block);
+
+    fun.body.inline(lambda);
+
     Ok(())
 }
+
+/// Shared inlining preconditions, checked regardless of how the call site was discovered
+/// (`useMemo`, or a general immediately-invoked function expression). Bails on constructs the
+/// splicing in [`inline_function_call`] can't safely handle.
+fn validate_inline_candidate(
+    lambda: &FunctionExpression,
+    call_site: &CallSite,
+) -> Result<(), Diagnostic> {
+    if lambda.lowered_function.is_async || lambda.lowered_function.is_generator {
+        return Err(Diagnostic::invalid_react(
+            "cannot inline an async or generator function",
+            None,
+        ));
+    }
+    if lambda.lowered_function.params.len() > MAX_INLINE_PARAMS {
+        return Err(Diagnostic::invalid_react(
+            "cannot inline a function with more than one parameter",
+            None,
+        ));
+    }
+    if uses_arguments_object(&lambda.lowered_function) {
+        return Err(Diagnostic::invalid_react(
+            "cannot inline a function that references `arguments`",
+            None,
+        ));
+    }
+    if references_own_result(lambda, call_site.instr_ix) {
+        return Err(Diagnostic::invalid_react(
+            "cannot inline a function that captures its own call result",
+            None,
+        ));
+    }
+    Ok(())
+}
+
+/// Scans for a reference to the implicit `arguments` object, which this module doesn't rewrite;
+/// like other magic globals (eg `useMemo` itself), it shows up as a `LoadGlobal`.
+fn uses_arguments_object(function: &Function) -> bool {
+    function.body.instructions.iter().any(|instr| {
+        matches!(
+            &instr.value,
+            InstructionValue::LoadGlobal(value) if value.name.as_str() == "arguments"
+        )
+    })
+}
+
+/// Best-effort recursion guard: if the lambda captures (as one of its `dependencies`) the very
+/// call instruction being inlined, the lambda references a variable whose initializer hasn't
+/// produced a value yet (eg `const x = (() => x)();`), so inlining it would be unsound. A full
+/// call-graph analysis would also catch indirect/mutual recursion through named bindings, but
+/// this repo doesn't have one yet; this catches the direct self-capture case.
+fn references_own_result(lambda: &FunctionExpression, call_instr_ix: InstrIx) -> bool {
+    lambda.dependencies.iter().any(|dependency| dependency.ix == call_instr_ix)
+}
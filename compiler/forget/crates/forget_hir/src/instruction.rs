@@ -3,8 +3,12 @@ use std::fmt::Display;
 use std::rc::Rc;
 
 use forget_estree::BinaryOperator;
+use num_bigint::BigInt;
+use num_traits::cast::FromPrimitive;
 
-use crate::{Function, IdentifierId, InstrIx, InstructionId, ScopeId, Type};
+use crate::{
+    Function, IdentifierId, InstrIx, InstructionId, MutVisitor, OperandRole, ScopeId, Type,
+};
 
 #[derive(Debug)]
 pub struct Instruction {
@@ -13,31 +17,17 @@ pub struct Instruction {
 }
 
 impl Instruction {
-    pub fn each_identifier_store<F>(&mut self, mut f: F) -> ()
+    pub fn each_identifier_store<F>(&mut self, f: F) -> ()
     where
         F: FnMut(&mut LValue) -> (),
     {
-        match &mut self.value {
-            InstructionValue::DeclareContext(instr) => {
-                f(&mut instr.lvalue);
-            }
-            InstructionValue::DeclareLocal(instr) => {
-                f(&mut instr.lvalue);
-            }
-            InstructionValue::StoreLocal(instr) => {
-                f(&mut instr.lvalue);
+        struct EachIdentifierStore<F>(F);
+        impl<F: FnMut(&mut LValue)> MutVisitor for EachIdentifierStore<F> {
+            fn visit_lvalue(&mut self, lvalue: &mut LValue) {
+                (self.0)(lvalue);
             }
-            InstructionValue::Array(_)
-            | InstructionValue::Binary(_)
-            | InstructionValue::Call(_)
-            | InstructionValue::LoadContext(_)
-            | InstructionValue::LoadGlobal(_)
-            | InstructionValue::LoadLocal(_)
-            | InstructionValue::Primitive(_)
-            | InstructionValue::Function(_)
-            | InstructionValue::JSXElement(_)
-            | InstructionValue::Tombstone => {}
         }
+        EachIdentifierStore(f).visit_instruction(self);
     }
 
     pub fn try_each_identifier_store<F, E>(&mut self, mut f: F) -> Result<(), E>
@@ -57,10 +47,15 @@ impl Instruction {
             InstructionValue::Array(_)
             | InstructionValue::Binary(_)
             | InstructionValue::Call(_)
+            | InstructionValue::ComputedLoad(_)
+            | InstructionValue::ComputedStore(_)
             | InstructionValue::LoadContext(_)
             | InstructionValue::LoadGlobal(_)
             | InstructionValue::LoadLocal(_)
+            | InstructionValue::Object(_)
             | InstructionValue::Primitive(_)
+            | InstructionValue::PropertyLoad(_)
+            | InstructionValue::PropertyStore(_)
             | InstructionValue::Function(_)
             | InstructionValue::JSXElement(_)
             | InstructionValue::Tombstone => {}
@@ -68,84 +63,32 @@ impl Instruction {
         Ok(())
     }
 
-    pub fn each_identifier_load<F>(&mut self, mut f: F) -> ()
+    pub fn each_identifier_load<F>(&mut self, f: F) -> ()
     where
         F: FnMut(&mut IdentifierOperand) -> (),
     {
-        match &mut self.value {
-            InstructionValue::LoadLocal(instr) => f(&mut instr.place),
-            InstructionValue::Array(_)
-            | InstructionValue::Binary(_)
-            | InstructionValue::Call(_)
-            | InstructionValue::DeclareContext(_)
-            | InstructionValue::DeclareLocal(_)
-            | InstructionValue::LoadContext(_)
-            | InstructionValue::LoadGlobal(_)
-            | InstructionValue::Primitive(_)
-            | InstructionValue::StoreLocal(_)
-            | InstructionValue::Function(_)
-            | InstructionValue::JSXElement(_)
-            | InstructionValue::Tombstone => {}
+        struct EachIdentifierLoad<F>(F);
+        impl<F: FnMut(&mut IdentifierOperand)> MutVisitor for EachIdentifierLoad<F> {
+            fn visit_identifier(&mut self, identifier: &mut IdentifierOperand, role: OperandRole) {
+                if role == OperandRole::Load {
+                    (self.0)(identifier);
+                }
+            }
         }
+        EachIdentifierLoad(f).visit_instruction(self);
     }
 
-    pub fn each_operand<F>(&mut self, mut f: F) -> ()
+    pub fn each_operand<F>(&mut self, f: F) -> ()
     where
         F: FnMut(&mut Operand) -> (),
     {
-        match &mut self.value {
-            InstructionValue::Array(value) => {
-                for item in &mut value.elements {
-                    match item {
-                        Some(PlaceOrSpread::Place(item)) => f(item),
-                        Some(PlaceOrSpread::Spread(item)) => f(item),
-                        None => {}
-                    }
-                }
-            }
-            InstructionValue::Binary(value) => {
-                f(&mut value.left);
-                f(&mut value.right);
-            }
-            InstructionValue::Call(value) => {
-                f(&mut value.callee);
-                for arg in &mut value.arguments {
-                    match arg {
-                        PlaceOrSpread::Place(item) => f(item),
-                        PlaceOrSpread::Spread(item) => f(item),
-                    }
-                }
+        struct EachOperand<F>(F);
+        impl<F: FnMut(&mut Operand)> MutVisitor for EachOperand<F> {
+            fn visit_operand(&mut self, operand: &mut Operand, _role: OperandRole) {
+                (self.0)(operand);
             }
-            InstructionValue::StoreLocal(value) => {
-                f(&mut value.value);
-            }
-            InstructionValue::Function(value) => {
-                for dep in &mut value.dependencies {
-                    f(dep)
-                }
-            }
-            InstructionValue::JSXElement(value) => {
-                f(&mut value.tag);
-                for attr in &mut value.props {
-                    match attr {
-                        JSXAttribute::Spread { argument } => f(argument),
-                        JSXAttribute::Attribute { name: _, value } => f(value),
-                    }
-                }
-                if let Some(children) = &mut value.children {
-                    for child in children {
-                        f(child)
-                    }
-                }
-            }
-            InstructionValue::DeclareContext(_)
-            | InstructionValue::LoadContext(_)
-            | InstructionValue::LoadGlobal(_)
-            | InstructionValue::DeclareLocal(_)
-            | InstructionValue::LoadLocal(_)
-            | InstructionValue::Primitive(_)
-            | InstructionValue::Tombstone => {}
         }
+        EachOperand(f).visit_instruction(self);
     }
 }
 
@@ -156,8 +99,8 @@ pub enum InstructionValue {
     Binary(Binary),
     Call(Call),
     // ComputedDelete(ComputedDelete),
-    // ComputedLoad(ComputedLoad),
-    // ComputedStore(ComputedStore),
+    ComputedLoad(ComputedLoad),
+    ComputedStore(ComputedStore),
     // Debugger(Debugger),
     DeclareContext(DeclareContext),
     DeclareLocal(DeclareLocal),
@@ -172,11 +115,11 @@ pub enum InstructionValue {
     // MethodCall(MethodCall),
     // New(New),
     // NextIterable(NextIterable),
-    // Object(Object),
+    Object(Object),
     Primitive(Primitive),
     // PropertyDelete(PropertyDelete),
-    // PropertyLoad(PropertyLoad),
-    // PropertyStore(PropertyStore),
+    PropertyLoad(PropertyLoad),
+    PropertyStore(PropertyStore),
     // RegExp(RegExp),
     // StoreContext(StoreContext),
     StoreLocal(StoreLocal),
@@ -192,7 +135,7 @@ pub struct Array {
     pub elements: Vec<Option<PlaceOrSpread>>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum PlaceOrSpread {
     Place(Operand),
     Spread(Operand),
@@ -211,6 +154,43 @@ pub struct Call {
     pub arguments: Vec<PlaceOrSpread>,
 }
 
+#[derive(Debug)]
+pub struct ComputedLoad {
+    pub object: Operand,
+    pub property: Operand,
+}
+
+#[derive(Debug)]
+pub struct ComputedStore {
+    pub object: Operand,
+    pub property: Operand,
+    pub value: Operand,
+}
+
+#[derive(Debug)]
+pub struct Object {
+    pub properties: Vec<ObjectProperty>,
+}
+
+#[derive(Debug)]
+pub enum ObjectProperty {
+    Property { key: String, value: Operand },
+    Spread { argument: Operand },
+}
+
+#[derive(Debug)]
+pub struct PropertyLoad {
+    pub object: Operand,
+    pub property: String,
+}
+
+#[derive(Debug)]
+pub struct PropertyStore {
+    pub object: Operand,
+    pub property: String,
+    pub value: Operand,
+}
+
 #[derive(Debug)]
 pub struct FunctionExpression {
     pub dependencies: Vec<Operand>,
@@ -224,6 +204,7 @@ pub struct Primitive {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PrimitiveValue {
+    BigInt(BigInt),
     Boolean(bool),
     Null,
     Number(Number),
@@ -234,6 +215,7 @@ pub enum PrimitiveValue {
 impl PrimitiveValue {
     pub fn is_truthy(&self) -> bool {
         match &self {
+            PrimitiveValue::BigInt(value) => *value != BigInt::from(0),
             PrimitiveValue::Boolean(value) => *value,
             PrimitiveValue::Number(value) => value.is_truthy(),
             PrimitiveValue::String(value) => value.len() != 0,
@@ -249,6 +231,7 @@ impl PrimitiveValue {
         match (&self, &other) {
             // 1. If Type(x) is Type(y), then
             //    a. Return IsStrictlyEqual(x, y).
+            (PrimitiveValue::BigInt(left), PrimitiveValue::BigInt(right)) => Some(left == right),
             (PrimitiveValue::Number(left), PrimitiveValue::Number(right)) => {
                 Some(left.equals(*right))
             }
@@ -257,6 +240,38 @@ impl PrimitiveValue {
             (PrimitiveValue::Boolean(left), PrimitiveValue::Boolean(right)) => Some(left == right),
             (PrimitiveValue::String(left), PrimitiveValue::String(right)) => Some(left == right),
 
+            // https://tc39.es/ecma262/multipage/abstract-operations.html#sec-bigint-numeric-types
+            // BigInt == Number compares the exact integer value; a non-integral, NaN, or
+            // infinite Number can never equal a BigInt.
+            (PrimitiveValue::BigInt(bigint), PrimitiveValue::Number(number))
+            | (PrimitiveValue::Number(number), PrimitiveValue::BigInt(bigint)) => {
+                let number = f64::from(*number);
+                if !number.is_finite() || number.fract() != 0.0 {
+                    Some(false)
+                } else {
+                    Some(BigInt::from_f64(number).as_ref() == Some(bigint))
+                }
+            }
+
+            // BigInt == String parses the string as a decimal integer (per StringToBigInt): an
+            // empty string is 0n, and any other non-integer string (including one with `_`
+            // separators) is not-equal rather than an error. Strings with a non-decimal prefix
+            // (`0x`, `0o`, `0b`, ...) are not handled and return None.
+            (PrimitiveValue::BigInt(bigint), PrimitiveValue::String(string))
+            | (PrimitiveValue::String(string), PrimitiveValue::BigInt(bigint)) => {
+                let string = string.trim();
+                let bytes = string.as_bytes();
+                if string.is_empty() {
+                    Some(*bigint == BigInt::from(0))
+                } else if bytes.len() > 1 && bytes[0] == b'0' && bytes[1].is_ascii_alphabetic() {
+                    None
+                } else if string.contains('_') {
+                    Some(false)
+                } else {
+                    Some(string.parse::<BigInt>().map_or(false, |value| value == *bigint))
+                }
+            }
+
             // 2. If x is null and y is undefined, return true.
             (PrimitiveValue::Null, PrimitiveValue::Undefined) => Some(true),
 
@@ -274,6 +289,9 @@ impl PrimitiveValue {
     pub fn strictly_equals(&self, other: &Self) -> bool {
         // https://tc39.es/ecma262/multipage/abstract-operations.html#sec-isstrictlyequal
         match (&self, &other) {
+            // Mixed BigInt/Number (or any other type) strict equality is always false: the
+            // arms below only match same-typed operands.
+            (PrimitiveValue::BigInt(left), PrimitiveValue::BigInt(right)) => left == right,
             (PrimitiveValue::Number(left), PrimitiveValue::Number(right)) => left.equals(*right),
             (PrimitiveValue::Null, PrimitiveValue::Null) => true,
             (PrimitiveValue::Undefined, PrimitiveValue::Undefined) => true,
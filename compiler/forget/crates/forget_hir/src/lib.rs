@@ -1,6 +1,9 @@
+mod assembler;
 mod basic_block;
 mod environment;
+mod eval;
 mod features;
+mod fold_constants;
 mod function;
 mod id_types;
 mod initialize;
@@ -10,10 +13,14 @@ mod print;
 mod registry;
 mod terminal;
 mod types;
+mod visitor;
 
+pub use assembler::{assemble, disassemble, AssembleError};
 pub use basic_block::*;
 pub use environment::*;
+pub use eval::{eval, RuntimeError};
 pub use features::*;
+pub use fold_constants::fold_constants;
 pub use function::*;
 pub use id_types::*;
 pub use initialize::{
@@ -27,3 +34,4 @@ pub use print::Print;
 pub use registry::Registry;
 pub use terminal::*;
 pub use types::*;
+pub use visitor::*;
@@ -0,0 +1,250 @@
+use crate::{
+    IdentifierOperand, Instruction, InstructionValue, JSXAttribute, LValue, ObjectProperty,
+    Operand, PlaceOrSpread,
+};
+
+/// Describes *why* a visitor is looking at a given operand or identifier, so that generic
+/// passes (liveness, mutability inference, ...) can tell reads from writes without having to
+/// re-derive it from the enclosing `InstructionValue` themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperandRole {
+    /// The instruction reads this value.
+    Load,
+    /// The instruction (re)binds this identifier (`DeclareLocal`/`DeclareContext`/`StoreLocal`),
+    /// or writes a property into this operand's object (`PropertyStore`/`ComputedStore`).
+    Store,
+    /// The instruction reads the value and retains a reference to it (eg a `StoreLocal`'s rhs,
+    /// or a closure's captured dependency).
+    Capture,
+}
+
+/// Read-only traversal of the HIR, following rustc MIR's `Visitor` design: `visit_instruction`
+/// and `visit_lvalue` default to recursing via the matching `walk_*` free function, and the leaf
+/// hooks `visit_operand`/`visit_identifier` default to doing nothing, so overriding
+/// `visit_operand` (say) sees every operand regardless of which instruction produced it,
+/// without every caller having to re-implement the match over `InstructionValue`.
+pub trait Visitor {
+    fn visit_instruction(&mut self, instr: &Instruction) {
+        walk_instruction(self, instr);
+    }
+
+    fn visit_operand(&mut self, _operand: &Operand, _role: OperandRole) {}
+
+    fn visit_lvalue(&mut self, lvalue: &LValue) {
+        walk_lvalue(self, lvalue);
+    }
+
+    fn visit_identifier(&mut self, _identifier: &IdentifierOperand, _role: OperandRole) {}
+}
+
+pub fn walk_lvalue<V: Visitor + ?Sized>(visitor: &mut V, lvalue: &LValue) {
+    visitor.visit_identifier(&lvalue.identifier, OperandRole::Store);
+}
+
+pub fn walk_instruction<V: Visitor + ?Sized>(visitor: &mut V, instr: &Instruction) {
+    match &instr.value {
+        InstructionValue::Array(value) => {
+            for item in value.elements.iter().flatten() {
+                match item {
+                    PlaceOrSpread::Place(item) => visitor.visit_operand(item, OperandRole::Load),
+                    PlaceOrSpread::Spread(item) => visitor.visit_operand(item, OperandRole::Load),
+                }
+            }
+        }
+        InstructionValue::Binary(value) => {
+            visitor.visit_operand(&value.left, OperandRole::Load);
+            visitor.visit_operand(&value.right, OperandRole::Load);
+        }
+        InstructionValue::Call(value) => {
+            visitor.visit_operand(&value.callee, OperandRole::Load);
+            for arg in &value.arguments {
+                match arg {
+                    PlaceOrSpread::Place(item) => visitor.visit_operand(item, OperandRole::Load),
+                    PlaceOrSpread::Spread(item) => visitor.visit_operand(item, OperandRole::Load),
+                }
+            }
+        }
+        InstructionValue::ComputedLoad(value) => {
+            visitor.visit_operand(&value.object, OperandRole::Load);
+            visitor.visit_operand(&value.property, OperandRole::Load);
+        }
+        InstructionValue::ComputedStore(value) => {
+            visitor.visit_operand(&value.object, OperandRole::Store);
+            visitor.visit_operand(&value.property, OperandRole::Load);
+            visitor.visit_operand(&value.value, OperandRole::Capture);
+        }
+        InstructionValue::DeclareContext(value) => {
+            visitor.visit_lvalue(&value.lvalue);
+        }
+        InstructionValue::DeclareLocal(value) => {
+            visitor.visit_lvalue(&value.lvalue);
+        }
+        InstructionValue::Function(value) => {
+            for dep in &value.dependencies {
+                visitor.visit_operand(dep, OperandRole::Capture);
+            }
+        }
+        InstructionValue::JSXElement(value) => {
+            visitor.visit_operand(&value.tag, OperandRole::Load);
+            for attr in &value.props {
+                match attr {
+                    JSXAttribute::Spread { argument } => {
+                        visitor.visit_operand(argument, OperandRole::Load)
+                    }
+                    JSXAttribute::Attribute { name: _, value } => {
+                        visitor.visit_operand(value, OperandRole::Load)
+                    }
+                }
+            }
+            if let Some(children) = &value.children {
+                for child in children {
+                    visitor.visit_operand(child, OperandRole::Load);
+                }
+            }
+        }
+        InstructionValue::LoadContext(value) => {
+            visitor.visit_operand(&value.place, OperandRole::Load);
+        }
+        InstructionValue::LoadLocal(value) => {
+            visitor.visit_identifier(&value.place, OperandRole::Load);
+        }
+        InstructionValue::Object(value) => {
+            for property in &value.properties {
+                match property {
+                    ObjectProperty::Property { key: _, value } => {
+                        visitor.visit_operand(value, OperandRole::Load)
+                    }
+                    ObjectProperty::Spread { argument } => {
+                        visitor.visit_operand(argument, OperandRole::Load)
+                    }
+                }
+            }
+        }
+        InstructionValue::PropertyLoad(value) => {
+            visitor.visit_operand(&value.object, OperandRole::Load);
+        }
+        InstructionValue::PropertyStore(value) => {
+            visitor.visit_operand(&value.object, OperandRole::Store);
+            visitor.visit_operand(&value.value, OperandRole::Capture);
+        }
+        InstructionValue::StoreLocal(value) => {
+            visitor.visit_lvalue(&value.lvalue);
+            visitor.visit_operand(&value.value, OperandRole::Capture);
+        }
+        InstructionValue::LoadGlobal(_) | InstructionValue::Primitive(_) | InstructionValue::Tombstone => {}
+    }
+}
+
+/// Mutable counterpart of [`Visitor`]; same default-recursing shape, but hooks receive `&mut`
+/// references so passes can rewrite operands/lvalues in place (eg constant folding, inlining).
+pub trait MutVisitor {
+    fn visit_instruction(&mut self, instr: &mut Instruction) {
+        walk_instruction_mut(self, instr);
+    }
+
+    fn visit_operand(&mut self, _operand: &mut Operand, _role: OperandRole) {}
+
+    fn visit_lvalue(&mut self, lvalue: &mut LValue) {
+        walk_lvalue_mut(self, lvalue);
+    }
+
+    fn visit_identifier(&mut self, _identifier: &mut IdentifierOperand, _role: OperandRole) {}
+}
+
+pub fn walk_lvalue_mut<V: MutVisitor + ?Sized>(visitor: &mut V, lvalue: &mut LValue) {
+    visitor.visit_identifier(&mut lvalue.identifier, OperandRole::Store);
+}
+
+pub fn walk_instruction_mut<V: MutVisitor + ?Sized>(visitor: &mut V, instr: &mut Instruction) {
+    match &mut instr.value {
+        InstructionValue::Array(value) => {
+            for item in value.elements.iter_mut().flatten() {
+                match item {
+                    PlaceOrSpread::Place(item) => visitor.visit_operand(item, OperandRole::Load),
+                    PlaceOrSpread::Spread(item) => visitor.visit_operand(item, OperandRole::Load),
+                }
+            }
+        }
+        InstructionValue::Binary(value) => {
+            visitor.visit_operand(&mut value.left, OperandRole::Load);
+            visitor.visit_operand(&mut value.right, OperandRole::Load);
+        }
+        InstructionValue::Call(value) => {
+            visitor.visit_operand(&mut value.callee, OperandRole::Load);
+            for arg in &mut value.arguments {
+                match arg {
+                    PlaceOrSpread::Place(item) => visitor.visit_operand(item, OperandRole::Load),
+                    PlaceOrSpread::Spread(item) => visitor.visit_operand(item, OperandRole::Load),
+                }
+            }
+        }
+        InstructionValue::ComputedLoad(value) => {
+            visitor.visit_operand(&mut value.object, OperandRole::Load);
+            visitor.visit_operand(&mut value.property, OperandRole::Load);
+        }
+        InstructionValue::ComputedStore(value) => {
+            visitor.visit_operand(&mut value.object, OperandRole::Store);
+            visitor.visit_operand(&mut value.property, OperandRole::Load);
+            visitor.visit_operand(&mut value.value, OperandRole::Capture);
+        }
+        InstructionValue::DeclareContext(value) => {
+            visitor.visit_lvalue(&mut value.lvalue);
+        }
+        InstructionValue::DeclareLocal(value) => {
+            visitor.visit_lvalue(&mut value.lvalue);
+        }
+        InstructionValue::Function(value) => {
+            for dep in &mut value.dependencies {
+                visitor.visit_operand(dep, OperandRole::Capture);
+            }
+        }
+        InstructionValue::JSXElement(value) => {
+            visitor.visit_operand(&mut value.tag, OperandRole::Load);
+            for attr in &mut value.props {
+                match attr {
+                    JSXAttribute::Spread { argument } => {
+                        visitor.visit_operand(argument, OperandRole::Load)
+                    }
+                    JSXAttribute::Attribute { name: _, value } => {
+                        visitor.visit_operand(value, OperandRole::Load)
+                    }
+                }
+            }
+            if let Some(children) = &mut value.children {
+                for child in children {
+                    visitor.visit_operand(child, OperandRole::Load);
+                }
+            }
+        }
+        InstructionValue::LoadContext(value) => {
+            visitor.visit_operand(&mut value.place, OperandRole::Load);
+        }
+        InstructionValue::LoadLocal(value) => {
+            visitor.visit_identifier(&mut value.place, OperandRole::Load);
+        }
+        InstructionValue::Object(value) => {
+            for property in &mut value.properties {
+                match property {
+                    ObjectProperty::Property { key: _, value } => {
+                        visitor.visit_operand(value, OperandRole::Load)
+                    }
+                    ObjectProperty::Spread { argument } => {
+                        visitor.visit_operand(argument, OperandRole::Load)
+                    }
+                }
+            }
+        }
+        InstructionValue::PropertyLoad(value) => {
+            visitor.visit_operand(&mut value.object, OperandRole::Load);
+        }
+        InstructionValue::PropertyStore(value) => {
+            visitor.visit_operand(&mut value.object, OperandRole::Store);
+            visitor.visit_operand(&mut value.value, OperandRole::Capture);
+        }
+        InstructionValue::StoreLocal(value) => {
+            visitor.visit_lvalue(&mut value.lvalue);
+            visitor.visit_operand(&mut value.value, OperandRole::Capture);
+        }
+        InstructionValue::LoadGlobal(_) | InstructionValue::Primitive(_) | InstructionValue::Tombstone => {}
+    }
+}